@@ -1,15 +1,15 @@
 #![feature(test)]
 extern crate test;
 
-use lf_queue::Queue;
+use lf_queue::{Queue, RawQueue};
 
-// cargo +nightly bench
+// cargo +nightly bench --features nightly
 #[cfg(test)]
 mod tests {
     use super::*;
     use test::Bencher;
 
-    // cargo +nightly bench --package lf-queue --bench queue -- tests::mpmc --exact
+    // cargo +nightly bench --features nightly --package lf-queue --bench queue -- tests::mpmc --exact
     //
     // Latest results:
     // - MacBook Air (M1, 2020): 260,718 ns/iter (+/- 16,344)
@@ -48,4 +48,175 @@ mod tests {
             }
         });
     }
+
+    // Compares the throughput of queues holding a different number of items per node. Items are
+    // pushed then popped from a single thread, so the cost of allocating and freeing the nodes
+    // isn't hidden by the contention between threads.
+    //
+    // cargo +nightly bench --features nightly --package lf-queue --bench queue -- tests::push_pop_node
+    fn push_pop_node<const N: usize>(b: &mut Bencher) {
+        const COUNT: usize = 10_000;
+        let queue: Queue<usize, N> = Queue::new();
+
+        b.iter(|| {
+            for i in 0..COUNT {
+                queue.push(i);
+            }
+
+            for _ in 0..COUNT {
+                test::black_box(queue.pop());
+            }
+        });
+    }
+
+    #[bench]
+    fn push_pop_node_7(b: &mut Bencher) {
+        push_pop_node::<7>(b);
+    }
+
+    #[bench]
+    fn push_pop_node_32(b: &mut Bencher) {
+        push_pop_node::<32>(b);
+    }
+
+    #[bench]
+    fn push_pop_node_128(b: &mut Bencher) {
+        push_pop_node::<128>(b);
+    }
+
+    #[bench]
+    fn push_pop_node_512(b: &mut Bencher) {
+        push_pop_node::<512>(b);
+    }
+
+    // Compares pushing items one at a time with pushing them by batches, which reserves a run of
+    // slots per node with a single update of the tail. Items are pushed then popped from a single
+    // thread, see `push_pop_node`.
+    //
+    // cargo +nightly bench --features nightly --package lf-queue --bench queue -- tests::push_batch
+    const BATCH_COUNT: usize = 10_000;
+    const BATCH_SIZE: usize = 100;
+
+    #[bench]
+    fn push_batch_per_item(b: &mut Bencher) {
+        let queue: Queue<usize> = Queue::new();
+
+        b.iter(|| {
+            for start in (0..BATCH_COUNT).step_by(BATCH_SIZE) {
+                for i in start..start + BATCH_SIZE {
+                    queue.push(i);
+                }
+            }
+
+            for _ in 0..BATCH_COUNT {
+                test::black_box(queue.pop());
+            }
+        });
+    }
+
+    #[bench]
+    fn push_batch_run(b: &mut Bencher) {
+        let queue: Queue<usize> = Queue::new();
+
+        b.iter(|| {
+            for start in (0..BATCH_COUNT).step_by(BATCH_SIZE) {
+                queue.push_batch(start..start + BATCH_SIZE);
+            }
+
+            for _ in 0..BATCH_COUNT {
+                test::black_box(queue.pop());
+            }
+        });
+    }
+
+    // cargo +nightly bench --features nightly --package lf-queue --bench queue -- tests::mpmc_blocking --exact
+    #[bench]
+    fn mpmc_blocking(b: &mut Bencher) {
+        const COUNT: usize = 1_000;
+        const CONCURRENCY: usize = 4;
+        let queue: Queue<usize> = Queue::new();
+
+        b.iter(|| {
+            let consumers: Vec<_> = (0..CONCURRENCY)
+                .map(|_| {
+                    let q = queue.clone();
+                    std::thread::spawn(move || {
+                        for _ in 0..COUNT {
+                            q.pop_blocking();
+                        }
+                    })
+                })
+                .collect();
+
+            let producers: Vec<_> = (0..CONCURRENCY)
+                .map(|_| {
+                    let q = queue.clone();
+                    std::thread::spawn(move || {
+                        for i in 0..COUNT {
+                            q.push(i);
+                        }
+                    })
+                })
+                .collect();
+
+            for th in producers.into_iter().chain(consumers) {
+                th.join().unwrap();
+            }
+        });
+    }
+
+    // Compares sharing the queue between scoped threads through the handles of a `Queue`, each
+    // one cloning the `Arc`, and through a reference to a `RawQueue`.
+    //
+    // cargo +nightly bench --features nightly --package lf-queue --bench queue -- tests::mpmc_scoped
+    const SCOPED_COUNT: usize = 1_000;
+    const SCOPED_CONCURRENCY: usize = 4;
+
+    #[bench]
+    fn mpmc_scoped_arc(b: &mut Bencher) {
+        let queue: Queue<usize> = Queue::new();
+
+        b.iter(|| {
+            std::thread::scope(|s| {
+                for _ in 0..SCOPED_CONCURRENCY {
+                    let q = queue.clone();
+                    s.spawn(move || {
+                        for _ in 0..SCOPED_COUNT {
+                            while q.pop().is_none() {}
+                        }
+                    });
+
+                    let q = queue.clone();
+                    s.spawn(move || {
+                        for i in 0..SCOPED_COUNT {
+                            q.push(i);
+                        }
+                    });
+                }
+            });
+        });
+    }
+
+    #[bench]
+    fn mpmc_scoped_raw(b: &mut Bencher) {
+        let queue: RawQueue<usize> = RawQueue::new();
+
+        b.iter(|| {
+            std::thread::scope(|s| {
+                for _ in 0..SCOPED_CONCURRENCY {
+                    s.spawn(|| {
+                        for _ in 0..SCOPED_COUNT {
+                            while queue.pop().is_none() {}
+                        }
+                    });
+
+                    s.spawn(|| {
+                        for i in 0..SCOPED_COUNT {
+                            queue.push(i);
+                        }
+                    });
+                }
+            });
+        });
+    }
 }
@@ -0,0 +1,20 @@
+use std::env;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // Declares the `loom` cfg, set by the model checking runs, as expected. Cfgs are
+    // only checked since Rust 1.80, and older versions warn about the directive.
+    if rustc_minor_version().map_or(false, |minor| minor >= 80) {
+        println!("cargo:rustc-check-cfg=cfg(loom)");
+    }
+}
+
+/// Returns the minor version of the compiler, e.g. 68 for `rustc 1.68.0`.
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    version.split('.').nth(1)?.parse().ok()
+}
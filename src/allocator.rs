@@ -0,0 +1,114 @@
+//! Defines where the [`Queue`] allocates its nodes.
+//!
+//! Nodes are allocated through a [`NodeAllocator`], which defaults to the
+//! [`Global`] allocator. With the `nightly` feature, any implementation of the
+//! unstable [`Allocator`] trait is a [`NodeAllocator`] as well.
+//!
+//! [`Allocator`]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html
+//! [`Queue`]: crate::queue::Queue
+
+use alloc::alloc::Layout;
+use core::ptr::NonNull;
+
+/// Allocates the memory of the nodes of a [`Queue`].
+///
+/// # Safety
+///
+/// A block of memory returned by [`NodeAllocator::allocate`] must remain valid, and
+/// must not be handed out again, until it's passed to
+/// [`NodeAllocator::deallocate`]. Nodes can be allocated and deallocated from
+/// any thread sharing the [`Queue`].
+///
+/// # Examples
+///
+/// ```
+/// use lf_queue::{NodeAllocator, Queue, Yield, DEFAULT_NODE_CAPACITY};
+/// use std::alloc::{GlobalAlloc, Layout, System};
+/// use std::ptr::NonNull;
+///
+/// /// Allocates the nodes with the allocator of the operating system.
+/// #[derive(Debug)]
+/// struct SystemNodes;
+///
+/// unsafe impl NodeAllocator for SystemNodes {
+///     fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+///         NonNull::new(unsafe { System.alloc(layout) })
+///     }
+///
+///     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+///         unsafe { System.dealloc(ptr.as_ptr(), layout) }
+///     }
+/// }
+///
+/// let queue = Queue::<usize, DEFAULT_NODE_CAPACITY, Yield, _>::new_in(SystemNodes);
+/// queue.push(1);
+///
+/// assert_eq!(queue.pop(), Some(1));
+/// ```
+///
+/// [`Queue`]: crate::queue::Queue
+pub unsafe trait NodeAllocator {
+    /// Allocates a block of memory fitting `layout`, or returns `None` if the
+    /// allocation failed.
+    ///
+    /// The size of `layout` is never zero.
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Deallocates a block of memory.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`NodeAllocator::allocate`] on this
+    /// allocator with the same `layout`, and must not be used anymore.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+/// The global memory allocator, used by a [`Queue`] which doesn't specify one.
+///
+/// With the `nightly` feature, it implements the unstable [`Allocator`] trait.
+///
+/// [`Allocator`]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html
+/// [`Queue`]: crate::queue::Queue
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Global;
+
+#[cfg(not(feature = "nightly"))]
+unsafe impl NodeAllocator for Global {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        NonNull::new(unsafe { alloc::alloc::alloc(layout) })
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { alloc::alloc::dealloc(ptr.as_ptr(), layout) }
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl alloc::alloc::Allocator for Global {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, alloc::alloc::AllocError> {
+        alloc::alloc::Allocator::allocate(&alloc::alloc::Global, layout)
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { alloc::alloc::Allocator::deallocate(&alloc::alloc::Global, ptr, layout) }
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<A: alloc::alloc::Allocator> NodeAllocator for A {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        alloc::alloc::Allocator::allocate(self, layout)
+            .ok()
+            .map(NonNull::cast)
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { alloc::alloc::Allocator::deallocate(self, ptr, layout) }
+    }
+}
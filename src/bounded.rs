@@ -0,0 +1,589 @@
+//! A lock-free multi-producer multi-consumer bounded queue.
+//!
+//! The items are stored into a ring of preallocated [`Slot`]s, allocated once when
+//! the [`BoundedQueue`] is created. The state of each slot holds a stamp reporting
+//! whether it's ready to be written or read, for a given lap around the ring:
+//!
+//! ```txt
+//! stamp == index              -> The slot is empty, and can be written by the producer
+//!                                claiming the tail index.
+//! stamp == index + 1          -> The slot is filled, and can be read by the consumer
+//!                                claiming the head index.
+//! stamp == index + ONE_LAP    -> The slot has been read, and can be written again
+//!                                during the next lap.
+//! ```
+//!
+//! The head and tail indexes are made of the offset of a slot in the ring, in their
+//! lower bits, and of the lap around the ring, in their upper bits. The bit between
+//! them is only set in the tail index, once the [`BoundedQueue`] is closed.
+//!
+//! [`Slot`]: crate::slot::Slot
+
+use crate::cache_pad::CachePad;
+use crate::error::PushError;
+use crate::slot::Slot;
+use crate::variant::sync::atomic::{fence, AtomicUsize, Ordering};
+use crate::variant::sync::Arc;
+use crate::wait::{WaitStrategy, Yield};
+use crate::waiter::Waiters;
+
+use alloc::boxed::Box;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::ops::ControlFlow;
+use core::ptr;
+
+/// A lock-free multi-producer multi-consumer bounded queue.
+///
+/// Unlike the [`Queue`], a [`BoundedQueue`] never allocates once created. When it's
+/// full, producers either fail to push, wait for some room, or overwrite the oldest
+/// item.
+///
+/// Like the [`Queue`], how a thread waits for another one to make progress is
+/// defined by the [`WaitStrategy`] `W`, which yields to the scheduler by default.
+///
+/// # Examples
+///
+/// ```
+/// use lf_queue::{BoundedQueue, PushError};
+///
+/// let queue = BoundedQueue::<usize>::new(2);
+///
+/// assert_eq!(queue.try_push(1), Ok(()));
+/// assert_eq!(queue.try_push(2), Ok(()));
+/// assert_eq!(queue.try_push(3), Err(PushError::Full(3)));
+///
+/// assert_eq!(queue.force_push(3), Ok(Some(1)));
+///
+/// assert_eq!(queue.pop(), Some(2));
+/// assert_eq!(queue.pop(), Some(3));
+/// assert_eq!(queue.pop(), None);
+/// ```
+///
+/// [`Queue`]: crate::queue::Queue
+/// [`WaitStrategy`]: crate::wait::WaitStrategy
+pub struct BoundedQueue<T, W = Yield> {
+    inner: Arc<Inner<T, W>>,
+}
+
+// Items are moved in and out of the queue by value, so a `BoundedQueue` can be shared
+// between threads as long as the items can be sent from one thread to another.
+unsafe impl<T: Send, W> Send for BoundedQueue<T, W> {}
+unsafe impl<T: Send, W> Sync for BoundedQueue<T, W> {}
+
+impl<T, W: WaitStrategy> BoundedQueue<T, W> {
+    /// Creates a new [`BoundedQueue`] able to hold up to `capacity` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::BoundedQueue;
+    ///
+    /// let queue = BoundedQueue::<usize>::new(100);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner::new(capacity)),
+        }
+    }
+
+    /// Returns the number of items the [`BoundedQueue`] is able to hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::BoundedQueue;
+    ///
+    /// let queue = BoundedQueue::<usize>::new(100);
+    /// assert_eq!(queue.capacity(), 100);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity
+    }
+
+    /// Closes the [`BoundedQueue`].
+    ///
+    /// Once closed, no more items can be pushed into the [`BoundedQueue`] while the
+    /// remaining ones can still be popped. Every producer blocked on a full
+    /// [`BoundedQueue`] is woken up right away and drops its item.
+    ///
+    /// Returns `true` if this call closed the [`BoundedQueue`], or `false` if it was
+    /// already closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{BoundedQueue, PushError};
+    ///
+    /// let queue = BoundedQueue::<usize>::new(2);
+    /// queue.try_push(1).unwrap();
+    ///
+    /// assert!(queue.close());
+    /// assert!(!queue.close());
+    ///
+    /// assert_eq!(queue.try_push(2), Err(PushError::Closed(2)));
+    /// assert_eq!(queue.pop(), Some(1));
+    /// ```
+    pub fn close(&self) -> bool {
+        self.inner.close()
+    }
+
+    /// Returns `true` if the [`BoundedQueue`] is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::BoundedQueue;
+    ///
+    /// let queue = BoundedQueue::<usize>::new(2);
+    /// assert!(!queue.is_closed());
+    ///
+    /// queue.close();
+    /// assert!(queue.is_closed());
+    /// ```
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Push an item into the [`BoundedQueue`], unless it's full or closed.
+    ///
+    /// Returns [`PushError::Full`] holding the item if the [`BoundedQueue`] is full,
+    /// or [`PushError::Closed`] if it has been closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{BoundedQueue, PushError};
+    ///
+    /// let queue = BoundedQueue::<usize>::new(1);
+    ///
+    /// assert_eq!(queue.try_push(1), Ok(()));
+    /// assert_eq!(queue.try_push(2), Err(PushError::Full(2)));
+    /// ```
+    pub fn try_push(&self, item: T) -> Result<(), PushError<T>> {
+        self.inner.try_push(item)
+    }
+
+    /// Push an item into the [`BoundedQueue`], blocking the current thread until
+    /// there is room for it.
+    ///
+    /// The thread is parked while the [`BoundedQueue`] is full and woken up by the
+    /// next pop. The item is dropped if the [`BoundedQueue`] is closed, see
+    /// [`BoundedQueue::try_push`] to get it back instead. This method requires the
+    /// `std` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::BoundedQueue;
+    /// use std::thread;
+    ///
+    /// let queue = BoundedQueue::<usize>::new(1);
+    /// queue.push(1);
+    ///
+    /// let q = queue.clone();
+    /// let th = thread::spawn(move || q.push(2));
+    ///
+    /// while queue.pop() != Some(1) {}
+    /// th.join().unwrap();
+    ///
+    /// assert_eq!(queue.pop(), Some(2));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn push(&self, item: T) {
+        let mut item = Some(item);
+
+        self.inner
+            .waiters
+            .wait_until(None, || match self.inner.try_push(item.take().unwrap()) {
+                Ok(()) | Err(PushError::Closed(_)) => Some(()),
+                Err(PushError::Full(i)) => {
+                    item = Some(i);
+                    None
+                }
+            })
+            .unwrap()
+    }
+
+    /// Push an item into the [`BoundedQueue`], overwriting the oldest item if it's full.
+    ///
+    /// Returns the overwritten item, if any, or [`PushError::Closed`] holding the item
+    /// if the [`BoundedQueue`] has been closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{BoundedQueue, PushError};
+    ///
+    /// let queue = BoundedQueue::<usize>::new(1);
+    ///
+    /// assert_eq!(queue.force_push(1), Ok(None));
+    /// assert_eq!(queue.force_push(2), Ok(Some(1)));
+    ///
+    /// queue.close();
+    /// assert_eq!(queue.force_push(3), Err(PushError::Closed(3)));
+    /// assert_eq!(queue.pop(), Some(2));
+    /// ```
+    pub fn force_push(&self, item: T) -> Result<Option<T>, PushError<T>> {
+        self.inner.force_push(item)
+    }
+
+    /// Pop an item from the [`BoundedQueue`]. Returns none if the [`BoundedQueue`]
+    /// is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::BoundedQueue;
+    ///
+    /// let queue = BoundedQueue::<usize>::new(10);
+    /// for i in 0..8 {
+    ///     queue.try_push(i).unwrap();
+    /// }
+    ///
+    /// for i in 0..8 {
+    ///     assert_eq!(i, queue.pop().unwrap());
+    /// }
+    ///
+    /// assert!(queue.pop().is_none());
+    /// ```
+    pub fn pop(&self) -> Option<T> {
+        self.inner.pop()
+    }
+}
+
+// Reports the approximate length of the queue, its capacity, and whether it's closed,
+// instead of the raw state of its slots.
+impl<T, W> fmt::Debug for BoundedQueue<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoundedQueue")
+            .field("len", &self.inner.len())
+            .field("capacity", &self.inner.capacity)
+            .field("closed", &self.inner.is_closed())
+            .finish()
+    }
+}
+
+impl<T, W> Clone for BoundedQueue<T, W> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+struct Inner<T, W> {
+    /// Reports the index of the next slot to read.
+    head: CachePad<AtomicUsize>,
+
+    /// Reports the index of the next slot to write.
+    tail: CachePad<AtomicUsize>,
+
+    /// Holds the preallocated slots.
+    buffer: Box<[Slot<T>]>,
+
+    /// Reports the number of slots.
+    capacity: usize,
+
+    /// The bit set in the tail index once the queue is closed. It's a power of two
+    /// greater than the capacity, so it's never part of the offset of a slot.
+    closed_bit: usize,
+
+    /// The value added to an index to move it to the same slot during the next lap.
+    /// It's the power of two above the closed bit, so an index is never mistaken for
+    /// the same slot during another lap.
+    one_lap: usize,
+
+    /// Parks the producers waiting for some room.
+    waiters: CachePad<Waiters>,
+
+    /// Indicates how the queue waits for other threads to make progress.
+    _wait: PhantomData<W>,
+}
+
+impl<T, W> Inner<T, W> {
+    /// Returns the number of items in the queue.
+    fn len(&self) -> usize {
+        loop {
+            // Loads the tail index twice to make sure the head index we load is
+            // consistent with it, i.e. they've both been loaded at the same time.
+            let tail = self.tail.load(Ordering::SeqCst);
+            let head = self.head.load(Ordering::SeqCst);
+            if self.tail.load(Ordering::SeqCst) == tail {
+                return self.distance(head, tail & !self.closed_bit);
+            }
+        }
+    }
+
+    /// Returns the number of slots from the `head` index to the `tail` index.
+    fn distance(&self, head: usize, tail: usize) -> usize {
+        let head_offset = head & (self.closed_bit - 1);
+        let tail_offset = tail & (self.closed_bit - 1);
+        let laps =
+            (tail & !(self.one_lap - 1)).wrapping_sub(head & !(self.one_lap - 1)) / self.one_lap;
+
+        // A forced push moves the tail before evicting the oldest item, so the tail
+        // may briefly be more than one lap ahead of the head.
+        (laps * self.capacity + tail_offset - head_offset).min(self.capacity)
+    }
+
+    fn is_closed(&self) -> bool {
+        self.tail.load(Ordering::SeqCst) & self.closed_bit != 0
+    }
+
+    fn close(&self) -> bool {
+        let tail = self.tail.fetch_or(self.closed_bit, Ordering::SeqCst);
+        if tail & self.closed_bit != 0 {
+            return false;
+        }
+
+        // Wakes up every producer waiting for some room, so they can report that the
+        // queue is closed.
+        self.waiters.notify_all();
+        true
+    }
+}
+
+impl<T, W: WaitStrategy> Inner<T, W> {
+    fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be non-zero");
+
+        // Each slot starts with a stamp equal to its index during the first lap, as
+        // all of them are ready to be written.
+        let buffer = (0..capacity).map(Slot::with_state).collect();
+
+        let closed_bit = (capacity + 1).next_power_of_two();
+
+        Self {
+            head: CachePad::new(AtomicUsize::new(0)),
+            tail: CachePad::new(AtomicUsize::new(0)),
+            buffer,
+            capacity,
+            closed_bit,
+            one_lap: closed_bit * 2,
+            waiters: CachePad::new(Waiters::new()),
+            _wait: PhantomData,
+        }
+    }
+
+    fn try_push(&self, item: T) -> Result<(), PushError<T>> {
+        self.push_or_else(item, |item, tail, _, _| {
+            let head = self.head.load(Ordering::Relaxed);
+
+            // If the head lags one lap behind the tail, the queue is full.
+            if head.wrapping_add(self.one_lap) == tail {
+                ControlFlow::Break(Err(PushError::Full(item)))
+            } else {
+                ControlFlow::Continue(item)
+            }
+        })
+        .map(|_| ())
+    }
+
+    fn force_push(&self, item: T) -> Result<Option<T>, PushError<T>> {
+        self.push_or_else(item, |item, tail, next_tail, slot| {
+            // Claims the slot by moving the tail first, which fails once the queue is
+            // closed, as the closed bit is part of the tail index.
+            if self
+                .tail
+                .compare_exchange_weak(tail, next_tail, Ordering::SeqCst, Ordering::Relaxed)
+                .is_err()
+            {
+                return ControlFlow::Continue(item);
+            }
+
+            let head = tail.wrapping_sub(self.one_lap);
+            let next_head = next_tail.wrapping_sub(self.one_lap);
+            let mut step = 0;
+
+            loop {
+                // Claims the oldest item by moving the head, so no consumer can read it.
+                if self
+                    .head
+                    .compare_exchange_weak(head, next_head, Ordering::SeqCst, Ordering::Relaxed)
+                    .is_ok()
+                {
+                    // Swaps the oldest item with the new one, and marks the slot as filled.
+                    let old = slot.item.with_mut(|p| unsafe {
+                        ptr::replace(p, MaybeUninit::new(item)).assume_init()
+                    });
+                    slot.state.store(tail.wrapping_add(1), Ordering::Release);
+                    return ControlFlow::Break(Ok(Some(old)));
+                }
+
+                // A consumer popped the oldest item first, so the slot is written as
+                // usual once the consumer is done with it.
+                if slot.state.load(Ordering::Acquire) == tail {
+                    slot.item
+                        .with_mut(|p| unsafe { p.write(MaybeUninit::new(item)) });
+                    slot.state.store(tail.wrapping_add(1), Ordering::Release);
+                    return ControlFlow::Break(Ok(None));
+                }
+
+                // The oldest item is being popped, or the previous slot is being
+                // claimed by another forced push, we let them make progress.
+                W::wait(step);
+                step = step.saturating_add(1);
+            }
+        })
+    }
+
+    /// Pushes an item, or calls `f` when the queue looks full.
+    ///
+    /// `f` is given the item, the current and next tail indexes, and the slot at the
+    /// tail. It either gives the item back to try again, or breaks with the result of
+    /// the push. Returns [`PushError::Closed`] holding the item once the queue is
+    /// closed.
+    fn push_or_else<F>(&self, mut item: T, f: F) -> Result<Option<T>, PushError<T>>
+    where
+        F: Fn(T, usize, usize, &Slot<T>) -> ControlFlow<Result<Option<T>, PushError<T>>, T>,
+    {
+        let mut tail = self.tail.load(Ordering::Relaxed);
+        let mut step = 0;
+
+        loop {
+            // As the closed bit is part of the tail index, closing the queue makes the
+            // exchanges on the tail fail.
+            if tail & self.closed_bit != 0 {
+                return Err(PushError::Closed(item));
+            }
+
+            let offset = tail & (self.closed_bit - 1);
+            let lap = tail & !(self.one_lap - 1);
+
+            // Moves to the next slot, or to the first slot of the next lap.
+            let next_tail = if offset + 1 < self.capacity {
+                tail + 1
+            } else {
+                lap.wrapping_add(self.one_lap)
+            };
+
+            let slot = unsafe { self.buffer.get_unchecked(offset) };
+            let stamp = slot.state.load(Ordering::Acquire);
+
+            if tail == stamp {
+                // The slot is empty, we try to claim it by moving the tail.
+                match self.tail.compare_exchange_weak(
+                    tail,
+                    next_tail,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        slot.item
+                            .with_mut(|p| unsafe { p.write(MaybeUninit::new(item)) });
+                        slot.state.store(tail.wrapping_add(1), Ordering::Release);
+                        return Ok(None);
+                    }
+                    Err(t) => tail = t,
+                }
+            } else if stamp.wrapping_add(self.one_lap) == tail.wrapping_add(1) {
+                // The slot still holds the item written during the previous lap,
+                // the queue might be full.
+                fence(Ordering::SeqCst);
+                item = match f(item, tail, next_tail, slot) {
+                    ControlFlow::Continue(item) => item,
+                    ControlFlow::Break(pushed) => return pushed,
+                };
+
+                // A consumer is reading the item, we let it make progress.
+                W::wait(step);
+                step = step.saturating_add(1);
+                tail = self.tail.load(Ordering::Relaxed);
+            } else {
+                // Another producer claimed the slot and the tail index is about
+                // to be updated.
+                W::wait(step);
+                step = step.saturating_add(1);
+                tail = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn pop(&self) -> Option<T> {
+        let mut head = self.head.load(Ordering::Relaxed);
+        let mut step = 0;
+
+        loop {
+            let offset = head & (self.closed_bit - 1);
+            let lap = head & !(self.one_lap - 1);
+
+            let slot = unsafe { self.buffer.get_unchecked(offset) };
+            let stamp = slot.state.load(Ordering::Acquire);
+
+            if head.wrapping_add(1) == stamp {
+                // The slot is filled, we try to claim it by moving the head.
+                let next_head = if offset + 1 < self.capacity {
+                    head + 1
+                } else {
+                    lap.wrapping_add(self.one_lap)
+                };
+
+                match self.head.compare_exchange_weak(
+                    head,
+                    next_head,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let item = slot.item.with(|p| unsafe { p.read().assume_init() });
+                        // Marks the slot as ready to be written during the next lap.
+                        slot.state
+                            .store(head.wrapping_add(self.one_lap), Ordering::Release);
+
+                        // Wakes up a producer waiting for some room, if any.
+                        self.waiters.notify_one();
+
+                        return Some(item);
+                    }
+                    Err(h) => head = h,
+                }
+            } else if stamp == head {
+                // The slot is empty, the queue might be empty.
+                fence(Ordering::SeqCst);
+                let tail = self.tail.load(Ordering::Relaxed);
+
+                if tail & !self.closed_bit == head {
+                    return None;
+                }
+
+                // A producer is writing the item, we let it make progress.
+                W::wait(step);
+                step = step.saturating_add(1);
+                head = self.head.load(Ordering::Relaxed);
+            } else {
+                // Another consumer claimed the slot and the head index is about
+                // to be updated.
+                W::wait(step);
+                step = step.saturating_add(1);
+                head = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T, W> Drop for Inner<T, W> {
+    fn drop(&mut self) {
+        if !mem::needs_drop::<T>() {
+            return;
+        }
+
+        // Having a mutable reference means no other thread can access the queue
+        // anymore, so all the slots between the head and the tail hold an item.
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Relaxed) & !self.closed_bit;
+        let head_offset = head & (self.closed_bit - 1);
+
+        for i in 0..self.distance(head, tail) {
+            let offset = (head_offset + i) % self.capacity;
+            let slot = &self.buffer[offset];
+            slot.item
+                .with_mut(|p| unsafe { ptr::drop_in_place((*p).as_mut_ptr()) });
+        }
+    }
+}
@@ -28,8 +28,8 @@
 //!   hw.cachelinesize: 128
 //!   ```
 
-use std::fmt;
-use std::ops::Deref;
+use core::fmt;
+use core::ops::Deref;
 
 /// Pads and aligns data to the length of a cache line.
 #[cfg_attr(any(target_arch = "x86_64", target_arch = "aarch64"), repr(align(128)))]
@@ -42,7 +42,7 @@ pub(crate) struct CachePad<T>(T);
 impl<T> CachePad<T> {
     /// Creates a padded representation of the data aligned with the
     /// length of a cache line.
-    pub(crate) fn new(t: T) -> CachePad<T> {
+    pub(crate) const fn new(t: T) -> CachePad<T> {
         CachePad(t)
     }
 }
@@ -0,0 +1,362 @@
+//! A multi-producer multi-consumer channel built on top of the [`Queue`].
+//!
+//! The channel keeps track of its [`Sender`] and [`Receiver`] handles separately, and
+//! closes the underlying queue once either side is gone: the receivers then report
+//! that the channel is disconnected once they've received the remaining items, while
+//! the senders fail to send any new item.
+//!
+//! [`Queue`]: crate::queue::Queue
+
+use crate::error::{
+    PopTimeoutError, RecvError, RecvTimeoutError, SendError, TryPopError, TryRecvError,
+};
+use crate::raw::RawQueue;
+use crate::variant::sync::atomic::{AtomicUsize, Ordering};
+use crate::variant::sync::Arc;
+
+use core::fmt;
+use std::time::{Duration, Instant};
+
+/// Creates a new channel, returning its [`Sender`] and [`Receiver`] halves.
+///
+/// Both halves can be cloned to send, or receive, items from several threads.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// let (tx, rx) = lf_queue::channel();
+///
+/// let th = thread::spawn(move || {
+///     for i in 0..3 {
+///         tx.send(i).unwrap();
+///     }
+/// });
+///
+/// th.join().unwrap();
+///
+/// // The only sender is gone, so the iterator ends once the items are received.
+/// assert_eq!(rx.iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+/// ```
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let chan = Arc::new(Chan {
+        inner: RawQueue::new(),
+        senders: AtomicUsize::new(1),
+        receivers: AtomicUsize::new(1),
+    });
+
+    (Sender { chan: chan.clone() }, Receiver { chan })
+}
+
+/// The state shared by both halves of a channel.
+struct Chan<T> {
+    inner: RawQueue<T>,
+
+    /// Reports the number of [`Sender`] handles.
+    senders: AtomicUsize,
+
+    /// Reports the number of [`Receiver`] handles.
+    receivers: AtomicUsize,
+}
+
+/// The sending half of a channel, created by [`channel`].
+///
+/// Items can be sent as long as at least one [`Receiver`] exists.
+pub struct Sender<T> {
+    chan: Arc<Chan<T>>,
+}
+
+// Items are moved in and out of the channel by value, so both halves can be shared
+// between threads as long as the items can be sent from one thread to another.
+unsafe impl<T: Send> Send for Sender<T> {}
+unsafe impl<T: Send> Sync for Sender<T> {}
+
+impl<T> Sender<T> {
+    /// Sends an item on the channel.
+    ///
+    /// Returns [`SendError`] holding the item if every [`Receiver`] has been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (tx, rx) = lf_queue::channel();
+    ///
+    /// assert!(tx.send(1).is_ok());
+    ///
+    /// drop(rx);
+    /// assert_eq!(tx.send(2).unwrap_err().0, 2);
+    /// ```
+    pub fn send(&self, item: T) -> Result<(), SendError<T>> {
+        self.chan
+            .inner
+            .try_push(item)
+            .map_err(|e| SendError(e.into_inner()))
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        let _ = self.chan.senders.fetch_add(1, Ordering::Relaxed);
+        Self {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        // The last sender disconnects the channel, letting the receivers know that
+        // no more items will be sent once they've received the remaining ones.
+        if self.chan.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            let _ = self.chan.inner.close();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a channel, created by [`channel`].
+///
+/// Items can be received as long as the channel isn't empty, or at least one
+/// [`Sender`] exists.
+pub struct Receiver<T> {
+    chan: Arc<Chan<T>>,
+}
+
+unsafe impl<T: Send> Send for Receiver<T> {}
+unsafe impl<T: Send> Sync for Receiver<T> {}
+
+impl<T> Receiver<T> {
+    /// Receives an item from the channel, blocking the current thread until an
+    /// item is available.
+    ///
+    /// Returns [`RecvError`] once the channel is empty and every [`Sender`] has
+    /// been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::RecvError;
+    /// use std::thread;
+    ///
+    /// let (tx, rx) = lf_queue::channel();
+    ///
+    /// let th = thread::spawn(move || tx.send(1).unwrap());
+    ///
+    /// assert_eq!(rx.recv(), Ok(1));
+    /// th.join().unwrap();
+    ///
+    /// assert_eq!(rx.recv(), Err(RecvError));
+    /// ```
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.chan.inner.pop_until(None).map_err(|_| RecvError)
+    }
+
+    /// Attempts to receive an item from the channel without blocking.
+    ///
+    /// Returns [`TryRecvError::Empty`] if the channel is empty, or
+    /// [`TryRecvError::Disconnected`] once the channel is empty and every [`Sender`]
+    /// has been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::TryRecvError;
+    ///
+    /// let (tx, rx) = lf_queue::channel::<usize>();
+    /// assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// drop(tx);
+    /// assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    /// ```
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        // Unlike `Queue::try_pop`, an item claimed by a sender which hasn't finished
+        // writing it is waited for, as the channel only reports empty or disconnected.
+        self.chan.inner.pop_result().map_err(|e| match e {
+            TryPopError::Closed => TryRecvError::Disconnected,
+            _ => TryRecvError::Empty,
+        })
+    }
+
+    /// Receives an item from the channel, blocking the current thread for at most
+    /// `timeout` until an item is available.
+    ///
+    /// Returns [`RecvTimeoutError::Timeout`] if the timeout elapsed before an item was
+    /// available, or [`RecvTimeoutError::Disconnected`] once the channel is empty and
+    /// every [`Sender`] has been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::RecvTimeoutError;
+    /// use std::time::Duration;
+    ///
+    /// let (tx, rx) = lf_queue::channel::<usize>();
+    ///
+    /// assert_eq!(
+    ///     rx.recv_timeout(Duration::from_millis(10)),
+    ///     Err(RecvTimeoutError::Timeout)
+    /// );
+    /// ```
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        // A deadline too far in the future to be represented is as good as none.
+        self.recv_until(Instant::now().checked_add(timeout))
+    }
+
+    /// Receives an item from the channel, blocking the current thread until an item
+    /// is available or the `deadline` is reached.
+    ///
+    /// Returns [`RecvTimeoutError::Timeout`] if the deadline was reached before an item
+    /// was available, or [`RecvTimeoutError::Disconnected`] once the channel is empty
+    /// and every [`Sender`] has been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::RecvTimeoutError;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let (tx, rx) = lf_queue::channel::<usize>();
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    ///
+    /// assert_eq!(rx.recv_deadline(deadline), Err(RecvTimeoutError::Timeout));
+    /// ```
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        self.recv_until(Some(deadline))
+    }
+
+    /// Returns an iterator blocking on the items of the channel, which ends once the
+    /// channel is disconnected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (tx, rx) = lf_queue::channel();
+    ///
+    /// tx.send(1).unwrap();
+    /// tx.send(2).unwrap();
+    /// drop(tx);
+    ///
+    /// assert_eq!(rx.iter().collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { rx: self }
+    }
+
+    /// Returns an iterator over the items available in the channel, which ends
+    /// without blocking once the channel is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (tx, rx) = lf_queue::channel();
+    ///
+    /// tx.send(1).unwrap();
+    /// tx.send(2).unwrap();
+    ///
+    /// assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    pub fn try_iter(&self) -> TryIter<'_, T> {
+        TryIter { rx: self }
+    }
+
+    fn recv_until(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        self.chan.inner.pop_until(deadline).map_err(|e| match e {
+            PopTimeoutError::Timeout => RecvTimeoutError::Timeout,
+            PopTimeoutError::Closed => RecvTimeoutError::Disconnected,
+        })
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        let _ = self.chan.receivers.fetch_add(1, Ordering::Relaxed);
+        Self {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        // The last receiver disconnects the channel so that the senders stop sending,
+        // and drops the items nobody is going to receive.
+        if self.chan.receivers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            let _ = self.chan.inner.close();
+            while self.chan.inner.pop().is_some() {}
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for Receiver<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { rx: self }
+    }
+}
+
+/// Iterator blocking on the items of a [`Receiver`], created by [`Receiver::iter`].
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    rx: &'a Receiver<T>,
+}
+
+impl<T> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+/// Iterator over the items available in a [`Receiver`], created by
+/// [`Receiver::try_iter`].
+#[derive(Debug)]
+pub struct TryIter<'a, T> {
+    rx: &'a Receiver<T>,
+}
+
+impl<T> Iterator for TryIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.try_recv().ok()
+    }
+}
+
+/// Iterator blocking on the items of a [`Receiver`], created by
+/// [`Receiver::into_iter`].
+#[derive(Debug)]
+pub struct IntoIter<T> {
+    rx: Receiver<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
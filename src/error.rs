@@ -0,0 +1,240 @@
+//! Errors reported by the [`Queue`] operations.
+//!
+//! [`Queue`]: crate::queue::Queue
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
+
+/// Error returned by [`Queue::try_pop`].
+///
+/// [`Queue::try_pop`]: crate::queue::Queue::try_pop
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryPopError {
+    /// The [`Queue`] is empty.
+    ///
+    /// [`Queue`]: crate::queue::Queue
+    Empty,
+
+    /// The next item has been claimed by a producer, or by another consumer,
+    /// which hasn't finished its operation yet.
+    Busy,
+
+    /// The [`Queue`] is both empty and closed.
+    ///
+    /// [`Queue`]: crate::queue::Queue
+    Closed,
+}
+
+impl fmt::Display for TryPopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryPopError::Empty => write!(f, "queue is empty"),
+            TryPopError::Busy => write!(f, "queue is busy"),
+            TryPopError::Closed => write!(f, "queue is empty and closed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for TryPopError {}
+
+/// Error returned by [`Queue::pop_timeout`] and [`Queue::pop_deadline`].
+///
+/// [`Queue::pop_timeout`]: crate::queue::Queue::pop_timeout
+/// [`Queue::pop_deadline`]: crate::queue::Queue::pop_deadline
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PopTimeoutError {
+    /// No item was available before the timeout elapsed.
+    Timeout,
+
+    /// The [`Queue`] is both empty and closed.
+    ///
+    /// [`Queue`]: crate::queue::Queue
+    Closed,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for PopTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PopTimeoutError::Timeout => write!(f, "timed out waiting on queue"),
+            PopTimeoutError::Closed => write!(f, "queue is empty and closed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for PopTimeoutError {}
+
+/// Error returned when an item can't be pushed into a [`Queue`] or a [`BoundedQueue`].
+///
+/// The item is handed back to the caller.
+///
+/// [`Queue`]: crate::queue::Queue
+/// [`BoundedQueue`]: crate::bounded::BoundedQueue
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PushError<T> {
+    /// The [`Queue`] or the [`BoundedQueue`] is closed.
+    ///
+    /// [`Queue`]: crate::queue::Queue
+    /// [`BoundedQueue`]: crate::bounded::BoundedQueue
+    Closed(T),
+
+    /// The [`BoundedQueue`] is full.
+    ///
+    /// [`BoundedQueue`]: crate::bounded::BoundedQueue
+    Full(T),
+}
+
+impl<T> PushError<T> {
+    /// Returns the item that couldn't be pushed.
+    pub fn into_inner(self) -> T {
+        match self {
+            PushError::Closed(item) | PushError::Full(item) => item,
+        }
+    }
+}
+
+// The item isn't required to implement `Debug`, so it isn't reported.
+impl<T> fmt::Debug for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::Closed(_) => write!(f, "Closed(..)"),
+            PushError::Full(_) => write!(f, "Full(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::Closed(_) => write!(f, "pushing into a closed queue"),
+            PushError::Full(_) => write!(f, "pushing into a full queue"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> error::Error for PushError<T> {}
+
+/// Error returned by [`Sender::send`] once every [`Receiver`] has been dropped.
+///
+/// The item is handed back to the caller.
+///
+/// [`Sender::send`]: crate::channel::Sender::send
+/// [`Receiver`]: crate::channel::Receiver
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+// The item isn't required to implement `Debug`, so it isn't reported.
+#[cfg(feature = "std")]
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sending on a closed channel")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> error::Error for SendError<T> {}
+
+/// Error returned by [`Receiver::recv`] once the channel is empty and every [`Sender`]
+/// has been dropped.
+///
+/// [`Receiver::recv`]: crate::channel::Receiver::recv
+/// [`Sender`]: crate::channel::Sender
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecvError;
+
+#[cfg(feature = "std")]
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "receiving on a closed channel")
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for RecvError {}
+
+/// Error returned by [`Receiver::try_recv`].
+///
+/// [`Receiver::try_recv`]: crate::channel::Receiver::try_recv
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// The channel is empty.
+    Empty,
+
+    /// The channel is empty and every [`Sender`] has been dropped.
+    ///
+    /// [`Sender`]: crate::channel::Sender
+    Disconnected,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "receiving on an empty channel"),
+            TryRecvError::Disconnected => write!(f, "receiving on a closed channel"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for TryRecvError {}
+
+#[cfg(feature = "std")]
+impl From<RecvError> for TryRecvError {
+    fn from(_: RecvError) -> Self {
+        TryRecvError::Disconnected
+    }
+}
+
+/// Error returned by [`Receiver::recv_timeout`] and [`Receiver::recv_deadline`].
+///
+/// [`Receiver::recv_timeout`]: crate::channel::Receiver::recv_timeout
+/// [`Receiver::recv_deadline`]: crate::channel::Receiver::recv_deadline
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    /// No item was available before the timeout elapsed.
+    Timeout,
+
+    /// The channel is empty and every [`Sender`] has been dropped.
+    ///
+    /// [`Sender`]: crate::channel::Sender
+    Disconnected,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => write!(f, "timed out waiting on channel"),
+            RecvTimeoutError::Disconnected => {
+                write!(f, "channel is empty and sending half is closed")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for RecvTimeoutError {}
+
+#[cfg(feature = "std")]
+impl From<RecvError> for RecvTimeoutError {
+    fn from(_: RecvError) -> Self {
+        RecvTimeoutError::Disconnected
+    }
+}
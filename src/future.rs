@@ -0,0 +1,144 @@
+//! Pops items from the [`Queue`] asynchronously.
+//!
+//! A task waiting for an item registers its [`Waker`] as a sleeper of the [`Queue`],
+//! which is woken up by the next push, or when the [`Queue`] is closed. This works with
+//! any executor.
+//!
+//! [`Queue`]: crate::queue::Queue
+
+use crate::allocator::{Global, NodeAllocator};
+use crate::error::TryPopError;
+use crate::node::DEFAULT_NODE_CAPACITY;
+use crate::queue::Queue;
+use crate::raw::RawQueue;
+use crate::wait::{WaitStrategy, Yield};
+
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures_core::Stream;
+
+/// Future returned by [`Queue::pop_async`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct PopFuture<
+    'a,
+    T,
+    const N: usize = DEFAULT_NODE_CAPACITY,
+    W = Yield,
+    A: NodeAllocator = Global,
+> {
+    inner: &'a RawQueue<T, N, W, A>,
+    id: Option<usize>,
+}
+
+impl<'a, T, const N: usize, W, A: NodeAllocator> PopFuture<'a, T, N, W, A> {
+    pub(crate) fn new(inner: &'a RawQueue<T, N, W, A>) -> Self {
+        Self { inner, id: None }
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator> Future for PopFuture<'_, T, N, W, A> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        poll_pop(this.inner, &mut this.id, cx)
+    }
+}
+
+impl<T, const N: usize, W, A: NodeAllocator> Drop for PopFuture<'_, T, N, W, A> {
+    fn drop(&mut self) {
+        cancel(self.inner, self.id);
+    }
+}
+
+impl<T, const N: usize, W, A: NodeAllocator> fmt::Debug for PopFuture<'_, T, N, W, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PopFuture").finish_non_exhaustive()
+    }
+}
+
+/// Stream returned by [`Queue::stream`].
+#[must_use = "streams do nothing unless polled"]
+pub struct PopStream<
+    T,
+    const N: usize = DEFAULT_NODE_CAPACITY,
+    W = Yield,
+    A: NodeAllocator = Global,
+> {
+    queue: Queue<T, N, W, A>,
+    id: Option<usize>,
+}
+
+impl<T, const N: usize, W, A: NodeAllocator> PopStream<T, N, W, A> {
+    pub(crate) fn new(queue: Queue<T, N, W, A>) -> Self {
+        Self { queue, id: None }
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator> Stream for PopStream<T, N, W, A> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        poll_pop(this.queue.inner(), &mut this.id, cx)
+    }
+}
+
+impl<T, const N: usize, W, A: NodeAllocator> Drop for PopStream<T, N, W, A> {
+    fn drop(&mut self) {
+        cancel(self.queue.inner(), self.id);
+    }
+}
+
+impl<T, const N: usize, W, A: NodeAllocator> fmt::Debug for PopStream<T, N, W, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PopStream").finish_non_exhaustive()
+    }
+}
+
+/// Pops an item, or registers the task to be woken up by the next push.
+///
+/// Resolves to `None` once the queue is both empty and closed.
+fn poll_pop<T, const N: usize, W: WaitStrategy, A: NodeAllocator>(
+    inner: &RawQueue<T, N, W, A>,
+    id: &mut Option<usize>,
+    cx: &mut Context<'_>,
+) -> Poll<Option<T>> {
+    loop {
+        let item = match inner.pop_result() {
+            Ok(item) => Some(item),
+            Err(TryPopError::Closed) => None,
+            Err(_) => {
+                // If the task is still registered, it waits for the next notification.
+                // Otherwise, it registers itself and checks the queue once again before
+                // waiting.
+                let (registered_id, registered) = inner.waiters.register(*id, cx.waker());
+                *id = Some(registered_id);
+                if registered {
+                    return Poll::Pending;
+                }
+
+                continue;
+            }
+        };
+
+        if let Some(id) = id.take() {
+            let _ = inner.waiters.unregister(id);
+        }
+
+        return Poll::Ready(item);
+    }
+}
+
+/// Unregisters a task which stops waiting for an item.
+fn cancel<T, const N: usize, W, A: NodeAllocator>(inner: &RawQueue<T, N, W, A>, id: Option<usize>) {
+    if let Some(id) = id {
+        // The task has been notified but won't pop the item, so we pass
+        // the notification on to another sleeper.
+        if !inner.waiters.unregister(id) {
+            inner.waiters.notify_one();
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! A lock-free log-linear histogram of latencies.
+//!
+//! Latencies are recorded in nanoseconds into buckets whose width grows with the
+//! latency. Each power of two is split into [`SUB_BUCKETS`] buckets of the same
+//! width, so that the bucket holding a latency is at most 12.5% wider than the
+//! latency itself, whatever its magnitude:
+//!
+//! ```txt
+//! [0, 1) [1, 2) ... [7, 8)     -> one nanosecond wide
+//! [8, 9) [9, 10) ... [15, 16)  -> one nanosecond wide
+//! [16, 18) ... [30, 32)        -> two nanoseconds wide
+//! [32, 36) ... [60, 64)        -> four nanoseconds wide
+//! ...
+//! ```
+//!
+//! Each bucket is an atomic counter, so recording a latency never blocks.
+
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+/// Reports how many bits of a latency, below its highest one, select its bucket
+/// within its power of two.
+const SUB_BUCKET_BITS: u32 = 3;
+
+/// Reports the number of buckets each power of two is split into.
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+
+/// Reports the number of buckets needed to hold any latency up to `u64::MAX`
+/// nanoseconds.
+const BUCKETS: usize = (u64::BITS - SUB_BUCKET_BITS + 1) as usize * SUB_BUCKETS;
+
+/// A lock-free log-linear histogram of latencies.
+///
+/// Recording a latency increments the counter of its bucket, and percentiles are
+/// computed from the counters, with a relative error of at most 12.5%.
+///
+/// # Examples
+///
+/// ```
+/// use lf_queue::LatencyHistogram;
+/// use std::time::Duration;
+///
+/// let histogram = LatencyHistogram::new();
+/// for us in 1..=100 {
+///     histogram.record(Duration::from_micros(us));
+/// }
+///
+/// assert_eq!(histogram.count(), 100);
+/// let p50 = histogram.percentile(50.0).unwrap();
+/// assert!(p50 >= Duration::from_micros(50) && p50 <= Duration::from_micros(57));
+///
+/// histogram.reset();
+/// assert_eq!(histogram.percentile(50.0), None);
+/// ```
+pub struct LatencyHistogram {
+    buckets: [AtomicU64; BUCKETS],
+    count: AtomicU64,
+    max: AtomicU64,
+}
+
+impl LatencyHistogram {
+    /// Creates an empty [`LatencyHistogram`].
+    pub fn new() -> Self {
+        Self {
+            buckets: core::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    /// Records a latency.
+    ///
+    /// Latencies longer than `u64::MAX` nanoseconds, about 584 years, are recorded
+    /// as `u64::MAX` nanoseconds.
+    pub fn record(&self, latency: Duration) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        let _ = self.buckets[bucket(nanos)].fetch_add(1, Ordering::Relaxed);
+        let _ = self.max.fetch_max(nanos, Ordering::Relaxed);
+        let _ = self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the number of recorded latencies.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Returns the longest recorded latency, or none if no latency has been recorded.
+    pub fn max(&self) -> Option<Duration> {
+        match self.count() {
+            0 => None,
+            _ => Some(Duration::from_nanos(self.max.load(Ordering::Relaxed))),
+        }
+    }
+
+    /// Returns the latency below which `percentile` percent of the recorded latencies
+    /// fall, or none if no latency has been recorded.
+    ///
+    /// The latency reported is the upper bound of the bucket holding the percentile,
+    /// which is never longer than the longest recorded latency.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `percentile` isn't between 0 and 100.
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        assert!(
+            (0.0..=100.0).contains(&percentile),
+            "a percentile must be between 0 and 100"
+        );
+
+        // Counters are read one after the other while latencies are being recorded,
+        // so the total is computed from the buckets themselves.
+        let counts: [u64; BUCKETS] =
+            core::array::from_fn(|i| self.buckets[i].load(Ordering::Relaxed));
+        let total = counts.iter().sum::<u64>();
+        if total == 0 {
+            return None;
+        }
+
+        // The rank of the latency we're looking for, starting from one.
+        let rank = ((percentile / 100.0 * total as f64).ceil() as u64).max(1);
+        let max = self.max.load(Ordering::Relaxed);
+
+        let mut seen = 0;
+        for (i, count) in counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(Duration::from_nanos(upper_bound(i).min(max)));
+            }
+        }
+
+        Some(Duration::from_nanos(max))
+    }
+
+    /// Removes every recorded latency.
+    ///
+    /// Latencies recorded while the [`LatencyHistogram`] is being reset may be partly
+    /// kept.
+    pub fn reset(&self) {
+        for bucket in self.buckets.iter() {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.max.store(0, Ordering::Relaxed);
+        self.count.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Printing every bucket would be unreadable, so only a summary is reported.
+impl fmt::Debug for LatencyHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LatencyHistogram")
+            .field("count", &self.count())
+            .field("p50", &self.percentile(50.0))
+            .field("p99", &self.percentile(99.0))
+            .field("max", &self.max())
+            .finish()
+    }
+}
+
+/// Returns the index of the bucket holding a latency of `nanos` nanoseconds.
+fn bucket(nanos: u64) -> usize {
+    if nanos < SUB_BUCKETS as u64 {
+        return nanos as usize;
+    }
+
+    // The power of two the latency falls into selects a group of buckets, and the
+    // bits following the highest one select a bucket within the group.
+    let shift = u64::BITS - 1 - nanos.leading_zeros() - SUB_BUCKET_BITS;
+    let sub_bucket = (nanos >> shift) as usize - SUB_BUCKETS;
+    (shift as usize + 1) * SUB_BUCKETS + sub_bucket
+}
+
+/// Returns the longest latency held by the bucket at `index`, in nanoseconds.
+fn upper_bound(index: usize) -> u64 {
+    if index < SUB_BUCKETS {
+        return index as u64;
+    }
+
+    let shift = (index / SUB_BUCKETS - 1) as u32;
+    let sub_bucket = (index % SUB_BUCKETS) as u64;
+    let lower_bound = (SUB_BUCKETS as u64 + sub_bucket) << shift;
+    lower_bound + ((1 << shift) - 1)
+}
@@ -0,0 +1,151 @@
+//! Iterators popping items from the [`Queue`] and the [`RawQueue`].
+//!
+//! None of them blocks: they end as soon as there is no item to pop, even though
+//! more items might be pushed later by another thread.
+//!
+//! [`Queue`]: crate::queue::Queue
+//! [`RawQueue`]: crate::raw::RawQueue
+
+use crate::allocator::{Global, NodeAllocator};
+use crate::node::DEFAULT_NODE_CAPACITY;
+use crate::queue::Queue;
+use crate::raw::RawQueue;
+use crate::wait::{WaitStrategy, Yield};
+
+use alloc::boxed::Box;
+use core::iter::FusedIterator;
+
+/// Iterator over the items of a [`Queue`], created by [`Queue::try_iter`].
+///
+/// [`Queue`]: crate::queue::Queue
+/// [`Queue::try_iter`]: crate::queue::Queue::try_iter
+#[derive(Debug)]
+pub struct PopIter<
+    'a,
+    T,
+    const N: usize = DEFAULT_NODE_CAPACITY,
+    W = Yield,
+    A: NodeAllocator = Global,
+> {
+    queue: &'a RawQueue<T, N, W, A>,
+}
+
+impl<'a, T, const N: usize, W, A: NodeAllocator> PopIter<'a, T, N, W, A> {
+    pub(crate) fn new(queue: &'a RawQueue<T, N, W, A>) -> Self {
+        Self { queue }
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator> Iterator for PopIter<'_, T, N, W, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.queue.pop()
+    }
+}
+
+/// Iterator over the items held by a [`Queue`] when [`Queue::drain`] was called.
+///
+/// [`Queue`]: crate::queue::Queue
+/// [`Queue::drain`]: crate::queue::Queue::drain
+#[derive(Debug)]
+pub struct Drain<
+    'a,
+    T,
+    const N: usize = DEFAULT_NODE_CAPACITY,
+    W = Yield,
+    A: NodeAllocator = Global,
+> {
+    queue: &'a RawQueue<T, N, W, A>,
+
+    /// Reports the maximum number of items left to pop.
+    remaining: usize,
+}
+
+impl<'a, T, const N: usize, W, A: NodeAllocator> Drain<'a, T, N, W, A> {
+    pub(crate) fn new(queue: &'a RawQueue<T, N, W, A>, remaining: usize) -> Self {
+        Self { queue, remaining }
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator> Iterator for Drain<'_, T, N, W, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        // Other consumers may have popped some of the items in the meantime, in which
+        // case the iterator ends early.
+        match self.queue.pop() {
+            Some(item) => {
+                self.remaining -= 1;
+                Some(item)
+            }
+            None => {
+                self.remaining = 0;
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator> FusedIterator for Drain<'_, T, N, W, A> {}
+
+/// Iterator over the items of a [`Queue`], created by [`Queue::into_iter`].
+///
+/// [`Queue`]: crate::queue::Queue
+/// [`Queue::into_iter`]: crate::queue::Queue::into_iter
+#[derive(Debug)]
+pub struct IntoPopIter<
+    T,
+    const N: usize = DEFAULT_NODE_CAPACITY,
+    W = Yield,
+    A: NodeAllocator = Global,
+> {
+    queue: Owner<T, N, W, A>,
+}
+
+/// Holds the [`Queue`] an [`IntoPopIter`] pops the items from.
+#[derive(Debug)]
+enum Owner<T, const N: usize, W, A: NodeAllocator> {
+    /// The iterator held the last handle of the [`Queue`], so no other thread can use
+    /// it anymore.
+    Exclusive(Box<RawQueue<T, N, W, A>>),
+
+    /// Other handles of the [`Queue`] exist.
+    Shared(Queue<T, N, W, A>),
+}
+
+impl<T, const N: usize, W, A: NodeAllocator> IntoPopIter<T, N, W, A> {
+    pub(crate) fn new(queue: Queue<T, N, W, A>) -> Self {
+        let queue = match queue.try_into_raw() {
+            Ok(inner) => Owner::Exclusive(Box::new(inner)),
+            Err(queue) => Owner::Shared(queue),
+        };
+        Self { queue }
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator> Iterator for IntoPopIter<T, N, W, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match &mut self.queue {
+            Owner::Exclusive(inner) => inner.pop_mut(),
+            Owner::Shared(queue) => queue.pop(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.queue {
+            Owner::Exclusive(inner) => (inner.len(), Some(inner.len())),
+            Owner::Shared(_) => (0, None),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+#![no_std]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
 #![deny(
     warnings,
     rustdoc::broken_intra_doc_links,
@@ -19,6 +21,10 @@
 
 //! A lock-free multi-producer multi-consumer unbounded queue.
 //!
+//! The crate is `no_std` and only requires `alloc` when its default `std` feature is
+//! disabled. Threads then can't be parked, so the blocking pops and the channel
+//! are not available, and waiting threads spin instead of yielding to the scheduler.
+//!
 //! # Examples
 //!
 //! Single Producer - Single Consumer:
@@ -163,11 +169,57 @@
 //! assert!(queue.pop().is_none());
 //! ```
 
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod allocator;
+mod bounded;
+#[cfg(feature = "std")]
+mod channel;
+mod error;
+#[cfg(feature = "async")]
+mod future;
+#[cfg(feature = "latency")]
+mod histogram;
+mod iter;
+mod metrics;
 mod queue;
+mod raw;
+#[cfg(all(feature = "registry", not(loom)))]
+pub mod registry;
+#[cfg(not(loom))]
+mod static_queue;
+#[cfg(feature = "latency")]
+mod timed;
+mod wait;
 
 pub(crate) mod cache_pad;
 pub(crate) mod node;
+pub(crate) mod pool;
 pub(crate) mod slot;
 pub(crate) mod variant;
+pub(crate) mod waiter;
 
-pub use queue::Queue;
+pub use allocator::{Global, NodeAllocator};
+pub use bounded::BoundedQueue;
+#[cfg(feature = "std")]
+pub use channel::{channel, IntoIter, Iter, Receiver, Sender, TryIter};
+#[cfg(feature = "std")]
+pub use error::{PopTimeoutError, RecvError, RecvTimeoutError, SendError, TryRecvError};
+pub use error::{PushError, TryPopError};
+#[cfg(feature = "async")]
+pub use future::{PopFuture, PopStream};
+#[cfg(feature = "latency")]
+pub use histogram::LatencyHistogram;
+pub use iter::{Drain, IntoPopIter, PopIter};
+#[cfg(feature = "metrics")]
+pub use metrics::QueueStats;
+pub use node::DEFAULT_NODE_CAPACITY;
+pub use queue::{Queue, QueueMut};
+pub use raw::RawQueue;
+#[cfg(not(loom))]
+pub use static_queue::StaticQueue;
+#[cfg(feature = "latency")]
+pub use timed::TimedQueue;
+pub use wait::{Backoff, ParkAfter, Spin, WaitStrategy, Yield};
@@ -0,0 +1,358 @@
+//! Counts the operations of the [`Queue`], to see how contended it is.
+//!
+//! With the `metrics` feature, each [`Metrics`] holds one set of counters per shard.
+//! A thread is assigned a shard the first time it records an event, and keeps
+//! updating the counters of that shard, so that threads using the [`Queue`] don't
+//! contend on the counters themselves. Reading the counters sums up every shard.
+//!
+//! Without the `metrics` feature, [`Metrics`] doesn't hold any counter and recording
+//! an event does nothing.
+//!
+//! A [`QueueStats`] can be exported in the [OpenMetrics] text format, which can be
+//! scraped by Prometheus. Each field is a metric family named after it, e.g.
+//! `lf_queue_pushes`, whose samples are labeled with the name of their queue.
+//!
+//! [OpenMetrics]: https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md
+//!
+//! [`Queue`]: crate::queue::Queue
+
+#[cfg(feature = "metrics")]
+use crate::cache_pad::CachePad;
+
+#[cfg(feature = "metrics")]
+use core::fmt;
+#[cfg(feature = "metrics")]
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Reports an event counted by [`Metrics`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Event {
+    /// An item has been pushed.
+    Push,
+    /// An item has been popped.
+    Pop,
+    /// A pop found the queue empty.
+    EmptyPop,
+    /// A producer failed to update the tail index.
+    TailCasFailure,
+    /// A consumer failed to update the head index.
+    HeadCasFailure,
+    /// A thread waited for a slot to be filled, or for the next node to be installed.
+    Wait,
+    /// A node has been allocated.
+    NodeAlloc,
+    /// A node has been freed.
+    NodeFree,
+}
+
+/// Snapshot of the length of a [`Queue`] and of the operations it counted since its
+/// creation.
+///
+/// Counters are updated by each thread without synchronizing with the others, so a
+/// snapshot taken while other threads use the [`Queue`] may be slightly outdated.
+///
+/// This type requires the `metrics` feature.
+///
+/// # Examples
+///
+/// ```
+/// use lf_queue::Queue;
+///
+/// let queue = Queue::<usize>::new();
+/// queue.push(1);
+/// queue.pop();
+/// queue.pop();
+///
+/// let stats = queue.stats();
+/// assert_eq!(stats.pushes, 1);
+/// assert_eq!(stats.pops, 1);
+/// assert_eq!(stats.empty_pops, 1);
+/// ```
+///
+/// [`Queue`]: crate::queue::Queue
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct QueueStats {
+    /// Number of items in the queue, see [`Queue::len`].
+    ///
+    /// [`Queue::len`]: crate::queue::Queue::len
+    pub len: usize,
+
+    /// Number of items pushed.
+    pub pushes: u64,
+
+    /// Number of items popped.
+    pub pops: u64,
+
+    /// Number of pops which found the queue empty.
+    pub empty_pops: u64,
+
+    /// Number of times a producer failed to update the tail index because another
+    /// producer did it first.
+    pub tail_cas_failures: u64,
+
+    /// Number of times a consumer failed to update the head index because another
+    /// consumer did it first.
+    pub head_cas_failures: u64,
+
+    /// Number of times a thread waited, through its [`WaitStrategy`], for another one:
+    /// a consumer waiting for a producer to fill a slot, or a producer or a consumer
+    /// waiting for the next node to be installed.
+    ///
+    /// [`WaitStrategy`]: crate::wait::WaitStrategy
+    pub waits: u64,
+
+    /// Number of nodes allocated. Nodes taken from the pool aren't allocated.
+    pub nodes_allocated: u64,
+
+    /// Number of nodes freed. Nodes kept in the pool aren't freed.
+    pub nodes_freed: u64,
+}
+
+#[cfg(feature = "metrics")]
+impl QueueStats {
+    /// Writes the statistics in the [OpenMetrics] text format, labeling each sample
+    /// with the `name` of the queue.
+    ///
+    /// The output is a complete exposition, terminated by an `# EOF` line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// queue.push_batch(0..3);
+    ///
+    /// let mut out = String::new();
+    /// queue.stats().write_openmetrics(&mut out, "ingest").unwrap();
+    ///
+    /// assert!(out.contains("lf_queue_length{queue=\"ingest\"} 3\n"));
+    /// assert!(out.contains("lf_queue_pushes_total{queue=\"ingest\"} 3\n"));
+    /// assert!(out.ends_with("# EOF\n"));
+    /// ```
+    ///
+    /// [OpenMetrics]: https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md
+    pub fn write_openmetrics(&self, w: &mut impl fmt::Write, name: &str) -> fmt::Result {
+        write_openmetrics(w, &[(name, *self)])?;
+        w.write_str("# EOF\n")
+    }
+}
+
+/// Describes a metric family exported from the fields of a [`QueueStats`].
+#[cfg(feature = "metrics")]
+struct Family {
+    name: &'static str,
+    kind: Kind,
+    help: &'static str,
+    value: fn(&QueueStats) -> u64,
+}
+
+/// Reports the type of a metric family.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Gauge,
+    Counter,
+}
+
+/// Lists the metric families exported for each queue, in order.
+#[cfg(feature = "metrics")]
+const FAMILIES: [Family; 9] = [
+    Family {
+        name: "lf_queue_length",
+        kind: Kind::Gauge,
+        help: "Number of items in the queue.",
+        value: |stats| stats.len as u64,
+    },
+    Family {
+        name: "lf_queue_pushes",
+        kind: Kind::Counter,
+        help: "Number of items pushed.",
+        value: |stats| stats.pushes,
+    },
+    Family {
+        name: "lf_queue_pops",
+        kind: Kind::Counter,
+        help: "Number of items popped.",
+        value: |stats| stats.pops,
+    },
+    Family {
+        name: "lf_queue_empty_pops",
+        kind: Kind::Counter,
+        help: "Number of pops which found the queue empty.",
+        value: |stats| stats.empty_pops,
+    },
+    Family {
+        name: "lf_queue_tail_cas_failures",
+        kind: Kind::Counter,
+        help: "Number of failed updates of the tail index.",
+        value: |stats| stats.tail_cas_failures,
+    },
+    Family {
+        name: "lf_queue_head_cas_failures",
+        kind: Kind::Counter,
+        help: "Number of failed updates of the head index.",
+        value: |stats| stats.head_cas_failures,
+    },
+    Family {
+        name: "lf_queue_waits",
+        kind: Kind::Counter,
+        help: "Number of waits of the producers and the consumers for a slot to be filled or for the next node to be installed.",
+        value: |stats| stats.waits,
+    },
+    Family {
+        name: "lf_queue_nodes_allocated",
+        kind: Kind::Counter,
+        help: "Number of nodes allocated.",
+        value: |stats| stats.nodes_allocated,
+    },
+    Family {
+        name: "lf_queue_nodes_freed",
+        kind: Kind::Counter,
+        help: "Number of nodes freed.",
+        value: |stats| stats.nodes_freed,
+    },
+];
+
+/// Writes the metric families of the given queues in the OpenMetrics text format,
+/// without terminating the exposition.
+///
+/// The samples of all the queues are grouped by family, as a family can't be
+/// described more than once.
+#[cfg(feature = "metrics")]
+pub(crate) fn write_openmetrics(
+    w: &mut impl fmt::Write,
+    queues: &[(&str, QueueStats)],
+) -> fmt::Result {
+    for family in FAMILIES.iter() {
+        let (kind, suffix) = match family.kind {
+            Kind::Gauge => ("gauge", ""),
+            Kind::Counter => ("counter", "_total"),
+        };
+
+        writeln!(w, "# TYPE {} {}", family.name, kind)?;
+        writeln!(w, "# HELP {} {}", family.name, family.help)?;
+        for (name, stats) in queues {
+            write!(w, "{}{}{{queue=\"", family.name, suffix)?;
+            write_label_value(w, name)?;
+            writeln!(w, "\"}} {}", (family.value)(stats))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a label value, escaping the characters which can't appear as is between
+/// its quotes.
+#[cfg(feature = "metrics")]
+fn write_label_value(w: &mut impl fmt::Write, value: &str) -> fmt::Result {
+    for c in value.chars() {
+        match c {
+            '\\' => w.write_str("\\\\")?,
+            '"' => w.write_str("\\\"")?,
+            '\n' => w.write_str("\\n")?,
+            c => w.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Reports the number of shards, each one holding a set of counters.
+#[cfg(feature = "metrics")]
+const SHARDS: usize = 16;
+
+/// Reports the number of events counted by each shard.
+#[cfg(feature = "metrics")]
+const EVENTS: usize = Event::NodeFree as usize + 1;
+
+#[cfg(feature = "metrics")]
+std::thread_local! {
+    /// The shard the current thread records its events in.
+    static SHARD: usize = {
+        static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+        NEXT_SHARD.fetch_add(1, Ordering::Relaxed) % SHARDS
+    };
+}
+
+/// Counts the events of a queue.
+#[cfg(feature = "metrics")]
+pub(crate) struct Metrics {
+    shards: [CachePad<[AtomicU64; EVENTS]>; SHARDS],
+}
+
+#[cfg(feature = "metrics")]
+impl Metrics {
+    /// Each shard starts with every counter set to zero.
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: CachePad<[AtomicU64; EVENTS]> = {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const COUNTER: AtomicU64 = AtomicU64::new(0);
+        CachePad::new([COUNTER; EVENTS])
+    };
+
+    pub(crate) const fn new() -> Self {
+        Self {
+            shards: [Self::ZERO; SHARDS],
+        }
+    }
+
+    /// Adds `count` occurrences of `event` to the shard of the current thread.
+    #[inline]
+    pub(crate) fn record(&self, event: Event, count: u64) {
+        if count == 0 {
+            return;
+        }
+
+        // The shard of a thread being destroyed can't be accessed anymore, so the
+        // event is recorded in the first shard.
+        let shard = SHARD.try_with(|shard| *shard).unwrap_or(0);
+        let _ = self.shards[shard][event as usize].fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Sums up the counters of every shard.
+    pub(crate) fn stats(&self) -> QueueStats {
+        let count = |event: Event| {
+            self.shards
+                .iter()
+                .map(|shard| shard[event as usize].load(Ordering::Relaxed))
+                .fold(0, u64::wrapping_add)
+        };
+
+        QueueStats {
+            len: 0,
+            pushes: count(Event::Push),
+            pops: count(Event::Pop),
+            empty_pops: count(Event::EmptyPop),
+            tail_cas_failures: count(Event::TailCasFailure),
+            head_cas_failures: count(Event::HeadCasFailure),
+            waits: count(Event::Wait),
+            nodes_allocated: count(Event::NodeAlloc),
+            nodes_freed: count(Event::NodeFree),
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Metrics").field(&self.stats()).finish()
+    }
+}
+
+/// Counts the events of a queue.
+#[cfg(not(feature = "metrics"))]
+#[derive(Debug)]
+pub(crate) struct Metrics {}
+
+#[cfg(not(feature = "metrics"))]
+impl Metrics {
+    pub(crate) const fn new() -> Self {
+        Self {}
+    }
+
+    /// Events aren't counted without the `metrics` feature.
+    #[inline(always)]
+    pub(crate) fn record(&self, _event: Event, _count: u64) {}
+}
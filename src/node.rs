@@ -1,35 +1,54 @@
 //! Holds a collection of [`Slot`].
 //!
-//! The [`Node`]'s container holds [`NODE_CAPACITY`] times [`Slot`]. Each [`Slot`]
-//! is used once before being scheduled for deletion. Each [`Node`] reports a pointer
-//! to the next [`Node`] of the [`Queue`] if any.
+//! The [`Node`]'s container holds `N` times [`Slot`]. Each [`Slot`] is used once
+//! before being scheduled for deletion. Each [`Node`] reports a pointer to the next
+//! [`Node`] of the [`Queue`] if any.
 //!
 //! [`Queue`]: crate::queue::Queue
 
 use crate::cache_pad::CachePad;
 use crate::slot::{Slot, DRAINING, READING};
 use crate::variant::sync::atomic::{AtomicPtr, Ordering};
-use crate::variant::thread;
+use crate::wait::WaitStrategy;
+
+use core::fmt;
 
 /// Holds a collection of [`Slot`].
-#[derive(Debug)]
-pub(crate) struct Node<T> {
+pub(crate) struct Node<T, const N: usize> {
     /// A pointer to the next [`Node`] of the [`Queue`] if any.
     ///
     /// [`Queue`]: crate::queue::Queue
-    pub(crate) next: AtomicPtr<CachePad<Node<T>>>,
+    pub(crate) next: AtomicPtr<CachePad<Node<T, N>>>,
 
     /// A collection of [`Slot`].
-    pub(crate) container: [Slot<T>; NODE_CAPACITY],
+    pub(crate) container: [Slot<T>; N],
 }
 
-impl<T> Node<T> {
+impl<T, const N: usize> Node<T, N> {
+    /// Reports the capacity (max number of item), a [`Node`] container can hold.
+    ///
+    /// A [`Node`] must be able to hold at least one item, otherwise the [`Queue`]
+    /// would never make progress.
+    ///
+    /// [`Queue`]: crate::queue::Queue
+    pub(crate) const CAPACITY: usize = {
+        assert!(N > 0, "a node must be able to hold at least one item");
+        N
+    };
+
+    /// Each [`Node`] holds [`Node::SIZE`] of indices.
+    ///
+    /// A [`Node`] has one index per [`Slot`] available in its container, plus one
+    /// index reached while the next [`Node`] is being installed.
+    pub(crate) const SIZE: usize = Self::CAPACITY + 1;
+
     /// New uninitialized [`Node`] are frequently added to the queue.
     /// Using a constant help us reducing the cost of this operation.
     #[cfg(not(loom))]
-    pub(crate) const UNINIT: Node<T> = Self {
-        next: AtomicPtr::new(std::ptr::null_mut()),
-        container: [Slot::UNINIT; NODE_CAPACITY],
+    #[allow(clippy::declare_interior_mutable_const)]
+    pub(crate) const UNINIT: Node<T, N> = Self {
+        next: AtomicPtr::new(core::ptr::null_mut()),
+        container: [Slot::UNINIT; N],
     };
 
     // Loom model checking can't work with constants as it needs to keep
@@ -39,28 +58,43 @@ impl<T> Node<T> {
     #[cfg(loom)]
     pub(crate) fn new() -> Self {
         Self {
-            next: AtomicPtr::new(std::ptr::null_mut()),
-            container: Default::default(),
+            next: AtomicPtr::new(core::ptr::null_mut()),
+            container: core::array::from_fn(|_| Slot::new()),
+        }
+    }
+
+    /// Resets a drained [`Node`] to its initial state, so it can be used again.
+    pub(crate) fn reset(&self) {
+        self.next.store(core::ptr::null_mut(), Ordering::Relaxed);
+        for slot in self.container.iter() {
+            slot.state.store(0, Ordering::Relaxed);
         }
     }
 
     /// Waits until the next pointer is set.
-    pub(crate) fn wait_next(&self) -> *mut CachePad<Self> {
+    ///
+    /// Returns the next pointer, along with the number of times the thread waited.
+    pub(crate) fn wait_next<W: WaitStrategy>(&self) -> (*mut CachePad<Self>, u32) {
+        let mut step = 0;
         loop {
             let next = self.next.load(Ordering::Acquire);
             if !next.is_null() {
-                return next;
+                return (next, step);
             }
-            thread::yield_now();
+            W::wait(step);
+            step = step.saturating_add(1);
         }
     }
 
-    /// Drain the [`Node`] container starting from `start` and drop the [`Node`] when possible.
-    pub(crate) unsafe fn drain(node: *mut CachePad<Self>, start: usize) {
+    /// Drain the [`Node`] container starting from `start`.
+    ///
+    /// Returns `true` when no thread is using the [`Node`] anymore, meaning the caller
+    /// is responsible for releasing it.
+    pub(crate) unsafe fn drain(node: *mut CachePad<Self>, start: usize) -> bool {
         // We don't need to set the `DRAINING` bit in the last slot because that slot has
         // begun the draining of the node.
-        for i in start..NODE_CAPACITY - 1 {
-            let slot = unsafe { (*node).container.get_unchecked(i) };
+        for i in start..Self::CAPACITY - 1 {
+            let slot = unsafe { (&*node).container.get_unchecked(i) };
 
             // Add the `DRAINING` bit if a thread is still using the slot (i.e., the
             // state is not `READING` now and after we add the `DRAINING` flag).
@@ -69,31 +103,39 @@ impl<T> Node<T> {
             if slot.state.load(Ordering::Acquire) & READING == 0
                 && slot.state.fetch_or(DRAINING, Ordering::AcqRel) & READING == 0
             {
-                return;
+                return false;
             }
         }
 
         // No thread is using the node, it's safe to destroy it.
-        drop(unsafe { Box::from_raw(node) });
+        true
     }
 }
 
-/// Each [`Node`] holds [`NODE_SIZE`] of indicies.
+// Reports the state of each slot, and whether the next node is installed, instead of
+// the address of the next node.
+impl<T, const N: usize> fmt::Debug for Node<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("container", &self.container)
+            .field("linked", &!self.next.load(Ordering::Acquire).is_null())
+            .finish()
+    }
+}
+
+/// Reports the default number of items held by each node of a [`Queue`], used when
+/// the [`Queue`] doesn't specify one.
 ///
-/// A [`Node`] as one reference to the next [`Node`] and [`NODE_SIZE`] - 1
-/// (i.e., [`NODE_CAPACITY`]) [`Slot`] available in its container.
+/// [`Queue`]: crate::queue::Queue
 #[cfg(not(loom))]
-pub(crate) const NODE_SIZE: usize = 8;
+pub const DEFAULT_NODE_CAPACITY: usize = 32;
 
-/// Each [`Node`] holds [`NODE_SIZE`] of indicies.
-///
-/// A [`Node`] as one reference to the next [`Node`] and [`NODE_SIZE`] - 1
-/// (i.e., [`NODE_CAPACITY`]) [`Slot`] available in its container.
+/// Reports the default number of items held by each node of a [`Queue`], used when
+/// the [`Queue`] doesn't specify one.
 ///
 /// When using loom, we shrink the size of the local queue. This shouldn't impact
 /// logic, but allows loom to test more edge cases in a reasonable a mount of time.
+///
+/// [`Queue`]: crate::queue::Queue
 #[cfg(loom)]
-pub(crate) const NODE_SIZE: usize = 4;
-
-/// Reports the capacity (max number of item), a [`Node`] container can hold.
-pub(crate) const NODE_CAPACITY: usize = NODE_SIZE - 1;
+pub const DEFAULT_NODE_CAPACITY: usize = 3;
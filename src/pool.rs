@@ -0,0 +1,152 @@
+//! Keeps drained [`Node`]s around so they can be reused by the [`Queue`].
+//!
+//! Instead of freeing a drained [`Node`], the [`Queue`] resets it and stores it into
+//! one of the empty cells of the [`NodePool`]. When a producer needs to install a new
+//! [`Node`], it takes one from the pool before falling back to an allocation.
+//!
+//! Each cell holds at most one [`Node`]. A [`Node`] is stored by exchanging a null cell
+//! with its pointer, and taken by swapping the cell with a null pointer, so a [`Node`]
+//! can't be taken twice.
+//!
+//! The [`NodePool`] also owns the [`NodeAllocator`] of the [`Queue`], so that every
+//! [`Node`] is allocated and freed through it, and recorded into the [`Metrics`] of
+//! the [`Queue`].
+//!
+//! [`Queue`]: crate::queue::Queue
+
+use crate::allocator::{Global, NodeAllocator};
+use crate::cache_pad::CachePad;
+use crate::metrics::{Event, Metrics};
+use crate::node::Node;
+use crate::variant::sync::atomic::{AtomicPtr, Ordering};
+
+use alloc::alloc::{handle_alloc_error, Layout};
+use alloc::vec::Vec;
+use core::ptr::{self, NonNull};
+
+/// Reports the default number of drained [`Node`]s a [`Queue`] keeps for reuse.
+///
+/// [`Queue`]: crate::queue::Queue
+pub(crate) const DEFAULT_POOL_CAPACITY: usize = 4;
+
+/// Holds up to a fixed number of drained [`Node`]s.
+///
+/// The [`Node`]s left in the pool aren't freed when it's dropped, the [`Queue`] frees
+/// them with [`NodePool::clear`] instead.
+///
+/// [`Queue`]: crate::queue::Queue
+#[derive(Debug)]
+pub(crate) struct NodePool<T, const N: usize, A: NodeAllocator = Global> {
+    cells: Vec<AtomicPtr<CachePad<Node<T, N>>>>,
+    allocator: A,
+}
+
+#[cfg(not(loom))]
+impl<T, const N: usize> NodePool<T, N> {
+    /// Creates a pool which can't hold any [`Node`], so drained ones are freed.
+    pub(crate) const fn empty() -> Self {
+        Self {
+            cells: Vec::new(),
+            allocator: Global,
+        }
+    }
+}
+
+impl<T, const N: usize, A: NodeAllocator> NodePool<T, N, A> {
+    /// Reports the memory layout of a [`Node`].
+    const LAYOUT: Layout = Layout::new::<CachePad<Node<T, N>>>();
+
+    pub(crate) fn new(capacity: usize, allocator: A) -> Self {
+        Self {
+            cells: (0..capacity)
+                .map(|_| AtomicPtr::new(ptr::null_mut()))
+                .collect(),
+            allocator,
+        }
+    }
+
+    /// Takes a [`Node`] out of the pool, or allocates a new one if the pool is empty.
+    pub(crate) fn alloc(&self, metrics: &Metrics) -> *mut CachePad<Node<T, N>> {
+        for cell in self.cells.iter() {
+            if !cell.load(Ordering::Relaxed).is_null() {
+                let node = cell.swap(ptr::null_mut(), Ordering::Acquire);
+                if !node.is_null() {
+                    return node;
+                }
+            }
+        }
+
+        #[cfg(not(loom))]
+        let node: Node<T, N> = Node::UNINIT;
+        #[cfg(loom)]
+        let node: Node<T, N> = Node::new();
+
+        let ptr = match self.allocator.allocate(Self::LAYOUT) {
+            Some(ptr) => ptr.cast::<CachePad<Node<T, N>>>().as_ptr(),
+            None => handle_alloc_error(Self::LAYOUT),
+        };
+        unsafe { ptr.write(CachePad::new(node)) };
+        metrics.record(Event::NodeAlloc, 1);
+        ptr
+    }
+
+    /// Frees a [`Node`].
+    ///
+    /// # Safety
+    ///
+    /// The [`Node`] must have been allocated by this pool, and no other thread may
+    /// use it anymore.
+    pub(crate) unsafe fn free(&self, node: *mut CachePad<Node<T, N>>, metrics: &Metrics) {
+        unsafe {
+            ptr::drop_in_place(node);
+            self.allocator
+                .deallocate(NonNull::new_unchecked(node).cast(), Self::LAYOUT);
+        }
+        metrics.record(Event::NodeFree, 1);
+    }
+
+    /// Frees a chain of [`Node`]s linked by their `next` pointer.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`NodePool::free`], for each [`Node`] of the chain.
+    pub(crate) unsafe fn free_chain(&self, mut node: *mut CachePad<Node<T, N>>, metrics: &Metrics) {
+        while !node.is_null() {
+            let next = unsafe { (&*node).next.load(Ordering::Relaxed) };
+            unsafe { self.free(node, metrics) };
+            node = next;
+        }
+    }
+
+    /// Resets a drained [`Node`] and stores it into the pool, or frees it if the pool
+    /// is full.
+    ///
+    /// # Safety
+    ///
+    /// No other thread may use the [`Node`] anymore.
+    pub(crate) unsafe fn recycle(&self, node: *mut CachePad<Node<T, N>>, metrics: &Metrics) {
+        unsafe { (&*node).reset() };
+
+        for cell in self.cells.iter() {
+            if cell.load(Ordering::Relaxed).is_null()
+                && cell
+                    .compare_exchange(ptr::null_mut(), node, Ordering::Release, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return;
+            }
+        }
+
+        unsafe { self.free(node, metrics) };
+    }
+
+    /// Frees all the [`Node`]s held by the pool.
+    pub(crate) fn clear(&self, metrics: &Metrics) {
+        for cell in self.cells.iter() {
+            let node = cell.swap(ptr::null_mut(), Ordering::Acquire);
+            if !node.is_null() {
+                unsafe { self.free(node, metrics) };
+            }
+        }
+    }
+}
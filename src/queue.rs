@@ -1,21 +1,133 @@
 //! A lock-free multi-producer multi-consumer unbounded queue.
 
-use crate::cache_pad::CachePad;
-use crate::node::{Node, NODE_CAPACITY, NODE_SIZE};
-use crate::slot::{DRAINING, FILLED, READING};
-use crate::variant::sync::atomic::{fence, AtomicPtr, AtomicUsize, Ordering};
+use crate::allocator::{Global, NodeAllocator};
+#[cfg(feature = "std")]
+use crate::error::PopTimeoutError;
+use crate::error::{PushError, TryPopError};
+#[cfg(feature = "async")]
+use crate::future::{PopFuture, PopStream};
+use crate::iter::{Drain, IntoPopIter, PopIter};
+#[cfg(feature = "metrics")]
+use crate::metrics::QueueStats;
+use crate::node::DEFAULT_NODE_CAPACITY;
+use crate::pool::DEFAULT_POOL_CAPACITY;
+use crate::raw::RawQueue;
 use crate::variant::sync::Arc;
-use crate::variant::thread;
+use crate::wait::{WaitStrategy, Yield};
 
-use std::mem::MaybeUninit;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
 
 /// A lock-free multi-producer multi-consumer unbounded queue.
-#[derive(Clone, Debug)]
-pub struct Queue<T> {
-    inner: Arc<Inner<T>>,
+///
+/// Items are stored into nodes holding up to `N` items each, allocated as the
+/// [`Queue`] grows and freed once all their items have been popped. A larger `N`
+/// reduces the number of allocations, at the cost of more memory held by the
+/// [`Queue`]. It defaults to [`DEFAULT_NODE_CAPACITY`]:
+///
+/// ```
+/// use lf_queue::Queue;
+///
+/// // Allocates a node every 128 items.
+/// let queue = Queue::<usize, 128>::new();
+/// queue.push(1);
+///
+/// assert_eq!(queue.pop(), Some(1));
+/// ```
+///
+/// A thread sometimes waits for another one to make progress, e.g. for a producer
+/// to finish writing the item it claimed. How it waits is defined by the
+/// [`WaitStrategy`] `W`, which yields to the scheduler by default:
+///
+/// ```
+/// use lf_queue::{Queue, Spin, DEFAULT_NODE_CAPACITY};
+///
+/// // Never gives up the time slice of the current thread.
+/// let queue = Queue::<usize, DEFAULT_NODE_CAPACITY, Spin>::new();
+/// queue.push(1);
+///
+/// assert_eq!(queue.pop(), Some(1));
+/// ```
+///
+/// Nodes are allocated with the [`NodeAllocator`] `A`, which defaults to the
+/// [`Global`] allocator, see [`Queue::new_in`].
+///
+/// Handles of a [`Queue`] share a [`RawQueue`] through an [`Arc`]. Threads which
+/// don't outlive the queue, e.g. scoped threads, can share a [`RawQueue`] by
+/// reference instead.
+///
+/// A node must be able to hold at least one item:
+///
+/// ```compile_fail
+/// use lf_queue::Queue;
+///
+/// let queue = Queue::<usize, 0>::new();
+/// queue.push(1);
+/// ```
+///
+/// A [`Queue`] can be sent to, and shared between, threads as long as its items
+/// can be sent to another thread:
+///
+/// ```compile_fail
+/// use lf_queue::Queue;
+/// use std::rc::Rc;
+/// use std::thread;
+///
+/// let queue = Queue::<Rc<usize>>::new();
+///
+/// thread::spawn(move || {
+///     queue.push(Rc::new(1));
+/// });
+/// ```
+///
+/// ```compile_fail
+/// use lf_queue::Queue;
+/// use std::rc::Rc;
+/// use std::thread;
+///
+/// let queue = Queue::<Rc<usize>>::new();
+/// let q = queue.clone();
+///
+/// thread::spawn(move || q.pop());
+/// ```
+///
+/// Items only need to be [`Send`] and not [`Sync`], as a single thread is
+/// able to access each item:
+///
+/// ```
+/// use lf_queue::Queue;
+/// use std::cell::Cell;
+/// use std::thread;
+///
+/// let queue = Queue::<Cell<usize>>::new();
+/// let q = queue.clone();
+///
+/// thread::spawn(move || q.push(Cell::new(1))).join().unwrap();
+///
+/// assert_eq!(queue.pop().unwrap().get(), 1);
+/// ```
+///
+/// [`Arc`]: alloc::sync::Arc
+/// [`DEFAULT_NODE_CAPACITY`]: crate::node::DEFAULT_NODE_CAPACITY
+/// [`Global`]: crate::allocator::Global
+/// [`NodeAllocator`]: crate::allocator::NodeAllocator
+/// [`RawQueue`]: crate::raw::RawQueue
+/// [`WaitStrategy`]: crate::wait::WaitStrategy
+pub struct Queue<T, const N: usize = DEFAULT_NODE_CAPACITY, W = Yield, A: NodeAllocator = Global> {
+    inner: Arc<RawQueue<T, N, W, A>>,
 }
 
-impl<T> Queue<T> {
+// Items are moved in and out of the queue by value, so a `Queue` can be shared
+// between threads as long as the items can be sent from one thread to another.
+unsafe impl<T: Send, const N: usize, W, A: NodeAllocator + Send + Sync> Send for Queue<T, N, W, A> {}
+unsafe impl<T: Send, const N: usize, W, A: NodeAllocator + Send + Sync> Sync for Queue<T, N, W, A> {}
+
+impl<T, const N: usize, W: WaitStrategy> Queue<T, N, W> {
     /// Creates a new [`Queue`].
     ///
     /// # Examples
@@ -26,9 +138,157 @@ impl<T> Queue<T> {
     /// let queue = Queue::<usize>::new();
     /// ```
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// Creates a new [`Queue`] keeping up to `capacity` drained nodes for reuse.
+    ///
+    /// Once all the items of a node have been popped, the node is kept to be used
+    /// again instead of being freed, saving an allocation and a free when the
+    /// [`Queue`] grows again. A [`Queue`] created with [`Queue::new`] keeps up to 4
+    /// nodes, while a `capacity` of zero frees every drained node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::with_pool_capacity(16);
+    /// ```
+    pub fn with_pool_capacity(capacity: usize) -> Self {
+        Self::with_pool_capacity_in(capacity, Global)
+    }
+
+    /// Creates a new [`Queue`] registered under `name`, so that its length and its
+    /// statistics are reported by [`registry::snapshot`].
+    ///
+    /// The registry only holds a weak reference to the cursors and the counters of
+    /// the [`Queue`], so the [`Queue`] can still be accessed exclusively through
+    /// [`Queue::get_mut`]. It's removed from the registry once its last handle is
+    /// dropped.
+    ///
+    /// This method requires the `registry` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{registry, Queue};
+    ///
+    /// let queue = Queue::<usize>::named("jobs");
+    /// queue.push(1);
+    ///
+    /// assert!(registry::snapshot()
+    ///     .iter()
+    ///     .any(|q| q.name == "jobs" && q.stats.len == 1));
+    /// ```
+    ///
+    /// [`registry::snapshot`]: crate::registry::snapshot
+    #[cfg(all(feature = "registry", not(loom)))]
+    pub fn named(name: impl Into<String>) -> Self
+    where
+        T: Send + 'static,
+    {
         Self {
-            inner: Arc::new(Inner::new()),
+            inner: Arc::new(RawQueue::named(name.into())),
+        }
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator> Queue<T, N, W, A> {
+    /// Creates a new [`Queue`] allocating its nodes with `allocator`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{Global, Queue};
+    ///
+    /// let queue = Queue::<usize>::new_in(Global);
+    /// ```
+    pub fn new_in(allocator: A) -> Self {
+        Self::with_pool_capacity_in(DEFAULT_POOL_CAPACITY, allocator)
+    }
+
+    /// Creates a new [`Queue`] allocating its nodes with `allocator`, and keeping up
+    /// to `capacity` drained nodes for reuse, see [`Queue::with_pool_capacity`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{Global, Queue};
+    ///
+    /// let queue = Queue::<usize>::with_pool_capacity_in(16, Global);
+    /// ```
+    pub fn with_pool_capacity_in(capacity: usize, allocator: A) -> Self {
+        Self {
+            inner: Arc::new(RawQueue::with_pool_capacity_in(capacity, allocator)),
+        }
+    }
+
+    /// Frees the drained nodes kept for reuse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::new();
+    ///
+    /// for i in 0..1_000 {
+    ///     queue.push(i);
+    /// }
+    /// while queue.pop().is_some() {}
+    ///
+    /// queue.shrink_to_fit();
+    /// ```
+    pub fn shrink_to_fit(&self) {
+        self.inner.shrink_to_fit();
+    }
+
+    /// Returns a [`QueueMut`] giving exclusive access to the [`Queue`], or none if other
+    /// handles of the [`Queue`] exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let mut queue = Queue::<usize>::new();
+    ///
+    /// let mut q = queue.get_mut().unwrap();
+    /// q.push(1);
+    /// assert_eq!(q.pop(), Some(1));
+    ///
+    /// let handle = queue.clone();
+    /// assert!(queue.get_mut().is_none());
+    /// ```
+    pub fn get_mut(&mut self) -> Option<QueueMut<'_, T, N, W, A>> {
+        Arc::get_mut(&mut self.inner).map(RawQueue::get_mut)
+    }
+
+    /// Consumes the [`Queue`] into a [`Vec`] holding its items in order, or gives the
+    /// [`Queue`] back if other handles of the [`Queue`] exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// queue.push_batch(0..3);
+    ///
+    /// let handle = queue.clone();
+    /// let queue = queue.try_into_vec().unwrap_err();
+    ///
+    /// drop(handle);
+    /// assert_eq!(queue.try_into_vec().unwrap(), vec![0, 1, 2]);
+    /// ```
+    pub fn try_into_vec(self) -> Result<Vec<T>, Self> {
+        let mut inner = self.try_into_raw()?;
+        let mut items = Vec::with_capacity(inner.len());
+        while let Some(item) = inner.pop_mut() {
+            items.push(item);
         }
+        Ok(items)
     }
 
     /// Push an item into the [`Queue`].
@@ -44,8 +304,209 @@ impl<T> Queue<T> {
     /// queue.push(2);
     /// queue.push(3);
     /// ```
+    ///
+    /// The item is dropped if the [`Queue`] is closed, see [`Queue::try_push`] to get
+    /// it back instead.
     pub fn push(&self, item: T) {
-        self.inner.push(item)
+        self.inner.push(item);
+    }
+
+    /// Push an item into the [`Queue`], unless the [`Queue`] is closed.
+    ///
+    /// Returns [`PushError::Closed`] holding the item if the [`Queue`] has been closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{PushError, Queue};
+    ///
+    /// let queue = Queue::<usize>::new();
+    ///
+    /// assert_eq!(queue.try_push(1), Ok(()));
+    ///
+    /// queue.close();
+    /// assert_eq!(queue.try_push(2), Err(PushError::Closed(2)));
+    /// ```
+    pub fn try_push(&self, item: T) -> Result<(), PushError<T>> {
+        self.inner.try_push(item)
+    }
+
+    /// Push a batch of items into the [`Queue`].
+    ///
+    /// Contrary to calling [`Queue::push`] for each item, which updates the tail of the
+    /// [`Queue`] once per item, the items are pushed by runs: a single update reserves
+    /// as many consecutive slots as the current node has left. Items pushed by other
+    /// threads in the meantime may be interleaved between two runs.
+    ///
+    /// The remaining items are dropped if the [`Queue`] is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// queue.push_batch(0..100);
+    ///
+    /// for i in 0..100 {
+    ///     assert_eq!(queue.pop(), Some(i));
+    /// }
+    /// ```
+    pub fn push_batch<I: IntoIterator<Item = T>>(&self, items: I) {
+        self.inner.push_batch(items);
+    }
+
+    /// Closes the [`Queue`].
+    ///
+    /// Once closed, no more items can be pushed into the [`Queue`] while the remaining
+    /// ones can still be popped. Every blocked consumer is woken up right away: they
+    /// pop the remaining items, then report that the [`Queue`] is closed once it's
+    /// empty.
+    ///
+    /// Returns `true` if this call closed the [`Queue`], or `false` if it was already
+    /// closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{Queue, TryPopError};
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// queue.push(1);
+    ///
+    /// assert!(queue.close());
+    /// assert!(!queue.close());
+    ///
+    /// assert_eq!(queue.try_pop(), Ok(1));
+    /// assert_eq!(queue.try_pop(), Err(TryPopError::Closed));
+    /// ```
+    pub fn close(&self) -> bool {
+        self.inner.close()
+    }
+
+    /// Returns `true` if the [`Queue`] is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// assert!(!queue.is_closed());
+    ///
+    /// queue.close();
+    /// assert!(queue.is_closed());
+    /// ```
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Returns the number of items in the [`Queue`].
+    ///
+    /// The length is computed from the head and tail of the [`Queue`], loaded at the
+    /// same time. While other threads push or pop items, it's only a snapshot which
+    /// may be outdated by the time it's returned. It counts the items being written by
+    /// a producer, while it doesn't count the ones being read by a consumer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// assert_eq!(queue.len(), 0);
+    ///
+    /// queue.push_batch(0..100);
+    /// assert_eq!(queue.len(), 100);
+    ///
+    /// queue.pop();
+    /// assert_eq!(queue.len(), 99);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Renders the chain of nodes of the [`Queue`], along with the state of each slot,
+    /// to help debugging tests.
+    ///
+    /// The first line reports the [`Queue`] like its [`Debug`] implementation does.
+    /// Each following line reports a node, starting from the head one, with the state
+    /// of its slots: `EMPTY` until a producer fills it, `FILLED` once it holds an item,
+    /// `READING` once a consumer started reading it, and `DRAINING` once the node is
+    /// scheduled to be freed. Nodes kept for reuse aren't reported.
+    ///
+    /// While other threads use the [`Queue`], the nodes may change as they're being
+    /// rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize, 2>::new();
+    /// queue.push_batch(0..3);
+    /// queue.pop();
+    ///
+    /// assert_eq!(
+    ///     queue.debug_dump(),
+    ///     "Queue { len: 2, head: 1, tail: 3, closed: false }\n\
+    ///      node 0: [FILLED|READING, FILLED]\n\
+    ///      node 1: [FILLED, EMPTY]\n"
+    /// );
+    /// ```
+    ///
+    /// [`Debug`]: core::fmt::Debug
+    pub fn debug_dump(&self) -> String {
+        self.inner.dump("Queue")
+    }
+
+    /// Returns `true` if the [`Queue`] is empty.
+    ///
+    /// Like [`Queue::len`], the result may be outdated by the time it's returned while
+    /// other threads push or pop items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// assert!(queue.is_empty());
+    ///
+    /// queue.push(1);
+    /// assert!(!queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns a snapshot of the length of the [`Queue`] and of the operations it counted
+    /// since its creation.
+    ///
+    /// The counters are shared by every handle of the [`Queue`]. Each thread updates
+    /// its own shard of the counters, so counting doesn't add any contention between
+    /// the threads using the [`Queue`].
+    ///
+    /// This method requires the `metrics` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize, 8>::new();
+    /// queue.push_batch(0..10);
+    /// while queue.pop().is_some() {}
+    ///
+    /// let stats = queue.stats();
+    /// assert_eq!(stats.pushes, 10);
+    /// assert_eq!(stats.pops, 10);
+    /// assert_eq!(stats.empty_pops, 1);
+    /// assert_eq!(stats.nodes_allocated, 2);
+    /// ```
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> QueueStats {
+        self.inner.stats()
     }
 
     /// Pop an item from the [`Queue`]. Returns none if the [`Queue`] is empty.
@@ -69,227 +530,388 @@ impl<T> Queue<T> {
     pub fn pop(&self) -> Option<T> {
         self.inner.pop()
     }
+
+    /// Pop up to `max` items from the [`Queue`] into `buf`. Returns the number of items
+    /// popped, which is zero if the [`Queue`] is empty.
+    ///
+    /// Contrary to calling [`Queue::pop`] for each item, which updates the head of the
+    /// [`Queue`] once per item, the items are claimed by runs: a single update claims
+    /// all the items the current node holds, up to `max`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// queue.push_batch(0..100);
+    ///
+    /// let mut buf = Vec::new();
+    /// assert_eq!(queue.pop_batch(&mut buf, 60), 60);
+    /// assert_eq!(queue.pop_batch(&mut buf, 60), 40);
+    /// assert_eq!(queue.pop_batch(&mut buf, 60), 0);
+    ///
+    /// assert_eq!(buf, (0..100).collect::<Vec<_>>());
+    /// ```
+    pub fn pop_batch(&self, buf: &mut Vec<T>, max: usize) -> usize {
+        self.inner.pop_batch(buf, max)
+    }
+
+    /// Returns an iterator popping the items of the [`Queue`], which ends once the
+    /// [`Queue`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// queue.push_batch(0..3);
+    ///
+    /// assert_eq!(queue.try_iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn try_iter(&self) -> PopIter<'_, T, N, W, A> {
+        self.inner.try_iter()
+    }
+
+    /// Returns an iterator popping the items held by the [`Queue`] when called.
+    ///
+    /// Contrary to [`Queue::try_iter`], the items pushed once the iterator has been
+    /// created are left in the [`Queue`], so the iterator ends even though producers
+    /// keep pushing items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// queue.push_batch(0..3);
+    ///
+    /// for i in queue.drain() {
+    ///     queue.push(i + 3);
+    /// }
+    ///
+    /// assert_eq!(queue.drain().collect::<Vec<_>>(), vec![3, 4, 5]);
+    /// ```
+    pub fn drain(&self) -> Drain<'_, T, N, W, A> {
+        self.inner.drain()
+    }
+
+    /// Try to pop an item from the [`Queue`] without waiting for any other thread.
+    ///
+    /// Contrary to [`Queue::pop`], which waits for the producer of the next item to
+    /// finish writing it, this method returns [`TryPopError::Busy`] when the next item
+    /// is not available yet. Returns [`TryPopError::Empty`] if the [`Queue`] is empty, or
+    /// [`TryPopError::Closed`] if the [`Queue`] is both empty and closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{Queue, TryPopError};
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// assert_eq!(queue.try_pop(), Err(TryPopError::Empty));
+    ///
+    /// queue.push(1);
+    /// assert_eq!(queue.try_pop(), Ok(1));
+    /// ```
+    pub fn try_pop(&self) -> Result<T, TryPopError> {
+        self.inner.try_pop()
+    }
+
+    /// Pop an item from the [`Queue`], blocking the current thread until an item
+    /// is available. Returns none once the [`Queue`] is both empty and closed.
+    ///
+    /// The thread is parked while the [`Queue`] is empty and woken up by the next push.
+    /// This method requires the `std` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    /// use std::thread;
+    ///
+    /// let queue = Queue::<usize>::new();
+    ///
+    /// let q = queue.clone();
+    /// let th = thread::spawn(move || q.pop_blocking());
+    ///
+    /// queue.push(1);
+    /// assert_eq!(th.join().unwrap(), Some(1));
+    ///
+    /// let q = queue.clone();
+    /// let th = thread::spawn(move || q.pop_blocking());
+    ///
+    /// queue.close();
+    /// assert_eq!(th.join().unwrap(), None);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn pop_blocking(&self) -> Option<T> {
+        self.inner.pop_blocking()
+    }
+
+    /// Pop an item from the [`Queue`], blocking the current thread for at most
+    /// `timeout` until an item is available.
+    ///
+    /// Returns [`PopTimeoutError::Timeout`] if the timeout elapsed before an item was
+    /// available, or [`PopTimeoutError::Closed`] once the [`Queue`] is both empty and
+    /// closed. This method requires the `std` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{PopTimeoutError, Queue};
+    /// use std::time::Duration;
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// assert_eq!(
+    ///     queue.pop_timeout(Duration::from_millis(10)),
+    ///     Err(PopTimeoutError::Timeout)
+    /// );
+    ///
+    /// queue.push(1);
+    /// assert_eq!(queue.pop_timeout(Duration::from_millis(10)), Ok(1));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn pop_timeout(&self, timeout: Duration) -> Result<T, PopTimeoutError> {
+        self.inner.pop_timeout(timeout)
+    }
+
+    /// Pop an item from the [`Queue`], blocking the current thread until an item
+    /// is available or the `deadline` is reached.
+    ///
+    /// Returns [`PopTimeoutError::Timeout`] if the deadline was reached before an item
+    /// was available, or [`PopTimeoutError::Closed`] once the [`Queue`] is both empty
+    /// and closed. This method requires the `std` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{PopTimeoutError, Queue};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    ///
+    /// assert_eq!(queue.pop_deadline(deadline), Err(PopTimeoutError::Timeout));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn pop_deadline(&self, deadline: Instant) -> Result<T, PopTimeoutError> {
+        self.inner.pop_deadline(deadline)
+    }
+
+    /// Pop an item from the [`Queue`] asynchronously.
+    ///
+    /// The returned future resolves once an item is available, or to none once the
+    /// [`Queue`] is both empty and closed. While the [`Queue`] is empty, the task is
+    /// woken up by the next push. This method requires the `async` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// # async fn run() {
+    /// let queue = Queue::<usize>::new();
+    /// queue.push(1);
+    ///
+    /// assert_eq!(queue.pop_async().await, Some(1));
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn pop_async(&self) -> PopFuture<'_, T, N, W, A> {
+        self.inner.pop_async()
+    }
+
+    /// Creates a [`Stream`] of the items popped from the [`Queue`].
+    ///
+    /// The stream holds a handle to the [`Queue`] and ends once the [`Queue`] is both
+    /// empty and closed. While the [`Queue`] is empty, the task polling the stream is
+    /// woken up by the next push. This method requires the `async` feature.
+    ///
+    /// [`Stream`]: futures_core::Stream
+    #[cfg(feature = "async")]
+    pub fn stream(&self) -> PopStream<T, N, W, A> {
+        PopStream::new(self.clone())
+    }
 }
 
-impl<T> Default for Queue<T> {
-    fn default() -> Self {
-        Self::new()
+impl<T, const N: usize, W, A: NodeAllocator> Queue<T, N, W, A> {
+    /// Takes the [`RawQueue`] out of the [`Queue`], or gives the [`Queue`] back if
+    /// other handles of the [`Queue`] exist.
+    pub(crate) fn try_into_raw(self) -> Result<RawQueue<T, N, W, A>, Self> {
+        Arc::try_unwrap(self.inner).map_err(|inner| Self { inner })
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn inner(&self) -> &RawQueue<T, N, W, A> {
+        &self.inner
     }
 }
 
+/// Exclusive access to a [`Queue`], created by [`Queue::get_mut`].
+///
+/// As no other thread can use the [`Queue`] in the meantime, items are pushed and
+/// popped without synchronizing with other threads.
 #[derive(Debug)]
-struct Inner<T> {
-    head: CachePad<Cursor<T>>,
-    tail: CachePad<Cursor<T>>,
+pub struct QueueMut<
+    'a,
+    T,
+    const N: usize = DEFAULT_NODE_CAPACITY,
+    W = Yield,
+    A: NodeAllocator = Global,
+> {
+    inner: &'a mut RawQueue<T, N, W, A>,
 }
 
-impl<T> Inner<T> {
-    fn new() -> Self {
-        #[cfg(not(loom))]
-        let node: Node<T> = Node::UNINIT;
-        #[cfg(loom)]
-        let node: Node<T> = Node::new();
+impl<'a, T, const N: usize, W, A: NodeAllocator> QueueMut<'a, T, N, W, A> {
+    pub(crate) fn new(inner: &'a mut RawQueue<T, N, W, A>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator> QueueMut<'_, T, N, W, A> {
+    /// Push an item into the [`Queue`].
+    ///
+    /// The item is dropped if the [`Queue`] is closed, see [`QueueMut::try_push`] to
+    /// get it back instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let mut queue = Queue::<usize>::new();
+    /// let mut q = queue.get_mut().unwrap();
+    ///
+    /// q.push(1);
+    /// assert_eq!(q.len(), 1);
+    /// ```
+    pub fn push(&mut self, item: T) {
+        let _ = self.inner.push_mut(item);
+    }
 
-        let first_node: *mut CachePad<Node<T>> = Box::into_raw(Box::new(CachePad::new(node)));
+    /// Push an item into the [`Queue`], unless the [`Queue`] is closed.
+    ///
+    /// Returns [`PushError::Closed`] holding the item if the [`Queue`] has been closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::{PushError, Queue};
+    ///
+    /// let mut queue = Queue::<usize>::new();
+    /// queue.close();
+    ///
+    /// let mut q = queue.get_mut().unwrap();
+    /// assert_eq!(q.try_push(1), Err(PushError::Closed(1)));
+    /// ```
+    pub fn try_push(&mut self, item: T) -> Result<(), PushError<T>> {
+        self.inner.push_mut(item).map_err(PushError::Closed)
+    }
 
+    /// Pop an item from the [`Queue`]. Returns none if the [`Queue`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let mut queue = Queue::<usize>::new();
+    /// queue.push(1);
+    ///
+    /// let mut q = queue.get_mut().unwrap();
+    /// assert_eq!(q.pop(), Some(1));
+    /// assert_eq!(q.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        self.inner.pop_mut()
+    }
+
+    /// Returns the number of items in the [`Queue`].
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the [`Queue`] is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+// Reports the approximate length of the queue, the logical positions of its head and
+// its tail, and whether it's closed, instead of the raw state of its nodes.
+impl<T, const N: usize, W, A: NodeAllocator> fmt::Debug for Queue<T, N, W, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt_state("Queue", f)
+    }
+}
+
+impl<T, const N: usize, W, A: NodeAllocator> Clone for Queue<T, N, W, A> {
+    fn clone(&self) -> Self {
         Self {
-            head: CachePad::new(Cursor {
-                index: AtomicUsize::new(0),
-                node: AtomicPtr::new(first_node),
-            }),
-            tail: CachePad::new(Cursor {
-                index: AtomicUsize::new(0),
-                node: AtomicPtr::new(first_node),
-            }),
+            inner: self.inner.clone(),
         }
     }
+}
 
-    fn push(&self, item: T) {
-        let mut tail_index = self.tail.index.load(Ordering::Acquire);
-        let mut tail_node = self.tail.node.load(Ordering::Acquire);
-
-        loop {
-            // Defines the node container offset of the slot where the provided item should be stored.
-            let offset = (tail_index >> MARK_BIT_SHIFT) % NODE_SIZE;
-
-            // If the node container is full, we wait until the next node is
-            // installed before moving forward and update our local reference.
-            if offset == NODE_CAPACITY {
-                thread::yield_now();
-                tail_index = self.tail.index.load(Ordering::Acquire);
-                tail_node = self.tail.node.load(Ordering::Acquire);
-                continue;
-            }
-
-            // Increments the tail index.
-            let next_tail_index = tail_index + (1 << MARK_BIT_SHIFT);
-            match self.tail.index.compare_exchange_weak(
-                tail_index,
-                next_tail_index,
-                Ordering::SeqCst,
-                Ordering::Acquire,
-            ) {
-                // The tail index has been updated successfully so we can now use
-                // the offset to store the item in the next available slot.
-                Ok(_) => unsafe {
-                    // If we're filling the last available slot of the node container,
-                    // we install a new one and update both the tail and the node to point
-                    // to this new node.
-                    if offset + 1 == NODE_CAPACITY {
-                        #[cfg(not(loom))]
-                        let node: Node<T> = Node::UNINIT;
-                        #[cfg(loom)]
-                        let node: Node<T> = Node::new();
-
-                        let next_node = Box::into_raw(Box::new(CachePad::new(node)));
-                        self.tail.node.store(next_node, Ordering::Release);
-                        let _ = self
-                            .tail
-                            .index
-                            .fetch_add(1 << MARK_BIT_SHIFT, Ordering::Release);
-                        (*tail_node).next.store(next_node, Ordering::Release);
-                    }
-
-                    // We can now safely store the provided item into the slot.
-                    let slot = (*tail_node).container.get_unchecked(offset);
-                    slot.item.with_mut(|p| p.write(MaybeUninit::new(item)));
-                    let _ = slot.state.fetch_or(FILLED, Ordering::Release);
-
-                    return;
-                },
-                // While trying to push the next item, the tail index
-                // has been updated by another thread. We update our local
-                // references with the value stored when we tried to make
-                // the exchange and what is now the current tail's node.
-                Err(current_tail_index) => {
-                    tail_index = current_tail_index;
-                    tail_node = self.tail.node.load(Ordering::Acquire);
-                }
-            }
-        }
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator + Default> Default for Queue<T, N, W, A> {
+    fn default() -> Self {
+        Self::new_in(A::default())
     }
+}
 
-    fn pop(&self) -> Option<T> {
-        let mut head_index = self.head.index.load(Ordering::Acquire);
-        let mut head_node = self.head.node.load(Ordering::Acquire);
-
-        loop {
-            // Defines the offset of the slot from where the next item should gathered.
-            let offset = (head_index >> MARK_BIT_SHIFT) % NODE_SIZE;
-
-            // If we reach the end of the node container, we wait until the next
-            // one is installed.
-            if offset == NODE_CAPACITY {
-                thread::yield_now();
-                head_index = self.head.index.load(Ordering::Acquire);
-                head_node = self.head.node.load(Ordering::Acquire);
-                continue;
-            }
-
-            // Increments the head index.
-            let mut next_head_index = head_index + (1 << MARK_BIT_SHIFT);
-
-            // If the mark bit is not set in the head index, we check if
-            // there is a pending item in the queue.
-            if next_head_index & MARK_BIT == 0 {
-                // Sync all threads and loads the current tail cursor.
-                fence(Ordering::SeqCst);
-                let tail_index = self.tail.index.load(Ordering::Acquire);
-
-                // If the head index equals the tail index, the queue is empty.
-                if head_index >> MARK_BIT_SHIFT == tail_index >> MARK_BIT_SHIFT {
-                    return None;
-                }
-
-                // If the head and the tail are not pointing to the same node,
-                // we set the `MARK_BIT` in the head to skip cheking if there
-                // is any item pending on the next iteration.
-                if (head_index >> MARK_BIT_SHIFT) / NODE_SIZE
-                    != (tail_index >> MARK_BIT_SHIFT) / NODE_SIZE
-                {
-                    next_head_index |= MARK_BIT;
-                }
-            }
-
-            // Try update the head index.
-            match self.head.index.compare_exchange_weak(
-                head_index,
-                next_head_index,
-                Ordering::SeqCst,
-                Ordering::Acquire,
-            ) {
-                // The head index has been updated successfully so we can now use
-                // the offset to pop the next item.
-                Ok(_) => unsafe {
-                    // If we're returning the last item of the node container, we
-                    // update the head cursor to point to the next node.
-                    if offset + 1 == NODE_CAPACITY {
-                        let next_node = (*head_node).wait_next();
-
-                        // Remove the mark bit if any and increment the index.
-                        let mut next_index =
-                            (next_head_index & !MARK_BIT).wrapping_add(1 << MARK_BIT_SHIFT);
-
-                        // If the next node points to another node, we can already
-                        // update the index to report that the next node that will
-                        // be installed is not the last one.
-                        if !(*next_node).next.load(Ordering::Relaxed).is_null() {
-                            next_index |= MARK_BIT;
-                        }
-
-                        self.head.node.store(next_node, Ordering::Release);
-                        self.head.index.store(next_index, Ordering::Release);
-                    }
-
-                    // Reads and returns the item.
-                    let slot = (*head_node).container.get_unchecked(offset);
-                    slot.wait_filled();
-                    let item = slot.item.with(|p| p.read().assume_init());
-
-                    // Drain and drop the node if we've reached the end of its container, or if another
-                    // thread wanted to do so but couldn't because this thread was busy reading from the slot.
-                    if offset + 1 == NODE_CAPACITY {
-                        Node::drain(head_node, 0);
-                    } else if slot.state.fetch_or(READING, Ordering::AcqRel) & DRAINING != 0 {
-                        Node::drain(head_node, offset + 1);
-                    }
-
-                    return Some(item);
-                },
-                // While trying to pop the next item, the head index
-                // has been updated by another thread. We update our local
-                // references with the value stored when we tried to make
-                // the exchange and what is now the current head's node.
-                Err(current_head_index) => {
-                    head_index = current_head_index;
-                    head_node = self.head.node.load(Ordering::Acquire);
-                }
-            }
-        }
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator> Extend<T> for Queue<T, N, W, A> {
+    /// Pushes the items with [`Queue::push_batch`].
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.push_batch(iter);
     }
 }
 
-#[derive(Debug)]
-struct Cursor<T> {
-    /// Reports the index of the next [`Slot`].
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator> IntoIterator for Queue<T, N, W, A> {
+    type Item = T;
+    type IntoIter = IntoPopIter<T, N, W, A>;
+
+    /// Creates an iterator popping the items of the [`Queue`], which ends once the
+    /// [`Queue`] is empty.
     ///
-    /// Its value is used to define the offset of the slot into the current
-    /// [`Node`] container by divinding it by the [`NODE_CAPACITY`].
+    /// If this is the last handle of the [`Queue`], no other thread can push or pop
+    /// items anymore, so the iterator yields all the items left in the [`Queue`],
+    /// popping them without synchronizing with other threads.
     ///
-    /// [`Slot`]: crate::slot::Slot
-    index: AtomicUsize,
-
-    /// Points to the current [`Node`].
-    node: AtomicPtr<CachePad<Node<T>>>,
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue = Queue::<usize>::new();
+    /// queue.push_batch(0..3);
+    ///
+    /// assert_eq!(queue.into_iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// ```
+    fn into_iter(self) -> IntoPopIter<T, N, W, A> {
+        IntoPopIter::new(self)
+    }
 }
 
-/// Defines how many lower bits are reserved for metadata.
-const MARK_BIT_SHIFT: usize = 1;
-
-/// The [`MARK_BIT`] indicates that the [`Node`] is not the last one.
-///
-/// The [`MARK_BIT`] helps to avoid loading the tail and head simultaneously
-/// to check whether or not the queue is empty when calling the `pop` method.
-///
-/// [`Node`]: crate::node::Node
-const MARK_BIT: usize = 1;
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator + Default> FromIterator<T>
+    for Queue<T, N, W, A>
+{
+    /// Creates a [`Queue`] holding the items, pushed with [`Queue::push_batch`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::Queue;
+    ///
+    /// let queue: Queue<usize> = (0..3).collect();
+    /// assert_eq!(queue.len(), 3);
+    /// ```
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let queue = Self::default();
+        queue.push_batch(iter);
+        queue
+    }
+}
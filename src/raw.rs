@@ -0,0 +1,1374 @@
+//! The core of the lock-free multi-producer multi-consumer unbounded queue.
+//!
+//! The [`RawQueue`] holds the head and tail cursors of the queue, pointing into a
+//! linked list of [`Node`]s. The [`Queue`] shares a [`RawQueue`] between its handles
+//! through an [`Arc`], while a [`RawQueue`] can be shared by reference on its own.
+//!
+//! [`Arc`]: alloc::sync::Arc
+//! [`Queue`]: crate::queue::Queue
+
+use crate::allocator::{Global, NodeAllocator};
+use crate::cache_pad::CachePad;
+#[cfg(feature = "std")]
+use crate::error::PopTimeoutError;
+use crate::error::{PushError, TryPopError};
+#[cfg(feature = "async")]
+use crate::future::PopFuture;
+use crate::iter::{Drain, PopIter};
+#[cfg(feature = "metrics")]
+use crate::metrics::QueueStats;
+use crate::metrics::{Event, Metrics};
+use crate::node::{Node, DEFAULT_NODE_CAPACITY};
+use crate::pool::{NodePool, DEFAULT_POOL_CAPACITY};
+use crate::queue::QueueMut;
+#[cfg(all(feature = "registry", not(loom)))]
+use crate::registry::{self, Introspect, Registration};
+use crate::slot::{DRAINING, FILLED, READING};
+use crate::variant::sync::atomic::{fence, AtomicPtr, AtomicUsize, Ordering};
+use crate::wait::{WaitStrategy, Yield};
+use crate::waiter::Waiters;
+
+use alloc::string::String;
+#[cfg(all(feature = "registry", not(loom)))]
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ops::Deref;
+use core::ptr;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// A lock-free multi-producer multi-consumer unbounded queue, shared by reference.
+///
+/// The [`RawQueue`] is the structure behind the [`Queue`], which shares it between
+/// its handles through an [`Arc`]. When the threads using the queue don't outlive
+/// it, e.g. scoped threads, the [`RawQueue`] can be shared by reference instead,
+/// without the indirection and the reference counting of the [`Arc`].
+///
+/// # Examples
+///
+/// ```
+/// use lf_queue::RawQueue;
+/// use std::thread;
+///
+/// let queue = RawQueue::<usize>::new();
+///
+/// thread::scope(|s| {
+///     s.spawn(|| queue.push_batch(0..100));
+///     s.spawn(|| queue.push_batch(100..200));
+/// });
+///
+/// let mut items = queue.try_iter().collect::<Vec<_>>();
+/// items.sort_unstable();
+///
+/// assert_eq!(items, (0..200).collect::<Vec<_>>());
+/// ```
+///
+/// [`Arc`]: alloc::sync::Arc
+/// [`Queue`]: crate::queue::Queue
+pub struct RawQueue<T, const N: usize = DEFAULT_NODE_CAPACITY, W = Yield, A: NodeAllocator = Global>
+{
+    /// Holds the cursors and the counters of the queue.
+    core: CoreRef<T, N>,
+
+    /// Reports the number of threads looking into the head [`Node`] before having
+    /// claimed one of its slots.
+    ///
+    /// While there is at least one of them, drained nodes can't be freed and are
+    /// retired instead.
+    peekers: CachePad<AtomicUsize>,
+
+    /// Points to the last retired [`Node`], if any. Retired nodes are linked using
+    /// their `next` pointer.
+    retired: AtomicPtr<CachePad<Node<T, N>>>,
+
+    /// Parks the consumers waiting for an item.
+    pub(crate) waiters: CachePad<Waiters>,
+
+    /// Keeps drained nodes to be used again.
+    pool: NodePool<T, N, A>,
+
+    /// Removes the queue from the registry once dropped, if it has been named.
+    #[cfg(all(feature = "registry", not(loom)))]
+    pub(crate) registration: Option<Registration>,
+
+    /// Indicates that the queue owns items of type `T`.
+    _marker: PhantomData<T>,
+
+    /// Indicates how the queue waits for other threads to make progress.
+    _wait: PhantomData<W>,
+}
+
+// Items are moved in and out of the queue by value, so a `RawQueue` can be shared
+// between threads as long as the items can be sent from one thread to another.
+// Nodes are allocated and freed by any thread using the queue.
+unsafe impl<T: Send, const N: usize, W, A: NodeAllocator + Send> Send for RawQueue<T, N, W, A> {}
+unsafe impl<T: Send, const N: usize, W, A: NodeAllocator + Sync> Sync for RawQueue<T, N, W, A> {}
+
+impl<T, const N: usize, W, A: NodeAllocator> RawQueue<T, N, W, A> {
+    /// Reports the number of indices covered by a [`Node`].
+    const NODE_SIZE: usize = Node::<T, N>::SIZE;
+
+    /// Reports the capacity (max number of item), a [`Node`] container can hold.
+    const NODE_CAPACITY: usize = Node::<T, N>::CAPACITY;
+
+    /// Adds a chain of nodes, linked by their `next` pointer, to the retired nodes.
+    unsafe fn retire(&self, first: *mut CachePad<Node<T, N>>, last: *mut CachePad<Node<T, N>>) {
+        let mut retired = self.retired.load(Ordering::Acquire);
+        loop {
+            unsafe { (&*last).next.store(retired, Ordering::Relaxed) };
+            match self.retired.compare_exchange_weak(
+                retired,
+                first,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return,
+                Err(current) => retired = current,
+            }
+        }
+    }
+
+    /// Returns the number of items in the [`RawQueue`], see [`Queue::len`].
+    ///
+    /// [`Queue::len`]: crate::queue::Queue::len
+    pub fn len(&self) -> usize {
+        self.core.len()
+    }
+
+    /// Renders the chain of nodes of the [`RawQueue`], see [`Queue::debug_dump`].
+    ///
+    /// [`Queue::debug_dump`]: crate::queue::Queue::debug_dump
+    pub fn debug_dump(&self) -> String {
+        self.dump("RawQueue")
+    }
+
+    /// Renders the chain of nodes, after the state of the queue as a struct named
+    /// `name`.
+    pub(crate) fn dump(&self, name: &str) -> String {
+        // Prevents the nodes we walk through from being freed until we're done.
+        let _guard = PeekGuard::new(self);
+
+        let mut dump = String::new();
+        let _ = writeln!(dump, "{:?}", State { queue: self, name });
+
+        let mut node = self.core.head.node.load(Ordering::Acquire);
+        let mut i = 0;
+        while !node.is_null() {
+            let _ = writeln!(dump, "node {}: {:?}", i, unsafe { &(&*node).container });
+            node = unsafe { (&*node).next.load(Ordering::Acquire) };
+            i += 1;
+        }
+
+        dump
+    }
+
+    /// Writes the approximate state of the [`RawQueue`] as a struct named `name`.
+    ///
+    /// Only the cursors are loaded, so that formatting the [`RawQueue`] never reads
+    /// an item.
+    pub(crate) fn fmt_state(&self, name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let head_index = self.core.head.index.load(Ordering::SeqCst);
+        let tail_index = self.core.tail.index.load(Ordering::SeqCst);
+
+        f.debug_struct(name)
+            .field("len", &self.len())
+            .field("head", &Self::position(head_index & !MARK_BIT))
+            .field("tail", &Self::position(tail_index & !CLOSED_BIT))
+            .field("closed", &(tail_index & CLOSED_BIT != 0))
+            .finish()
+    }
+
+    /// Returns the logical position of an index, i.e. the number of slots before it,
+    /// skipping the extra index of each node.
+    fn position(index: usize) -> usize {
+        let index = index >> MARK_BIT_SHIFT;
+        index - index / Self::NODE_SIZE
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy> RawQueue<T, N, W> {
+    /// Creates a new [`RawQueue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::RawQueue;
+    ///
+    /// let queue = RawQueue::<usize>::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// Creates a new [`RawQueue`] keeping up to `capacity` drained nodes for reuse,
+    /// see [`Queue::with_pool_capacity`].
+    ///
+    /// [`Queue::with_pool_capacity`]: crate::queue::Queue::with_pool_capacity
+    pub fn with_pool_capacity(capacity: usize) -> Self {
+        Self::with_pool_capacity_in(capacity, Global)
+    }
+
+    /// Creates a new [`RawQueue`] registered under `name`, see [`Queue::named`].
+    ///
+    /// [`Queue::named`]: crate::queue::Queue::named
+    #[cfg(all(feature = "registry", not(loom)))]
+    pub(crate) fn named(name: String) -> Self
+    where
+        T: 'static,
+    {
+        let core = Arc::new(Core::new());
+        let weak: Weak<dyn Introspect> = Arc::<Core<T, N>>::downgrade(&core);
+
+        let mut queue = Self::new();
+        queue.core = CoreRef::Shared(core);
+        queue.registration = Some(registry::register(name, weak));
+        queue
+    }
+
+    /// Creates an empty queue in a `const` context. Its pool can't hold any node, so
+    /// drained nodes are freed.
+    #[cfg(not(loom))]
+    pub(crate) const fn new_unpooled() -> Self {
+        Self {
+            core: CoreRef::new(Core::new()),
+            peekers: CachePad::new(AtomicUsize::new(0)),
+            retired: AtomicPtr::new(ptr::null_mut()),
+            waiters: CachePad::new(Waiters::new()),
+            pool: NodePool::empty(),
+            #[cfg(all(feature = "registry", not(loom)))]
+            registration: None,
+            _marker: PhantomData,
+            _wait: PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator> RawQueue<T, N, W, A> {
+    /// Creates a new [`RawQueue`] allocating its nodes with `allocator`, see
+    /// [`Queue::new_in`].
+    ///
+    /// [`Queue::new_in`]: crate::queue::Queue::new_in
+    pub fn new_in(allocator: A) -> Self {
+        Self::with_pool_capacity_in(DEFAULT_POOL_CAPACITY, allocator)
+    }
+
+    /// Creates a new [`RawQueue`] allocating its nodes with `allocator`, and keeping
+    /// up to `capacity` drained nodes for reuse.
+    ///
+    /// The first node is installed by the first push, so that creating a queue doesn't
+    /// allocate any node.
+    pub fn with_pool_capacity_in(capacity: usize, allocator: A) -> Self {
+        Self {
+            core: CoreRef::new(Core::new()),
+            peekers: CachePad::new(AtomicUsize::new(0)),
+            retired: AtomicPtr::new(ptr::null_mut()),
+            waiters: CachePad::new(Waiters::new()),
+            pool: NodePool::new(capacity, allocator),
+            #[cfg(all(feature = "registry", not(loom)))]
+            registration: None,
+            _marker: PhantomData,
+            _wait: PhantomData,
+        }
+    }
+
+    /// Frees the drained nodes kept for reuse.
+    pub fn shrink_to_fit(&self) {
+        self.pool.clear(&self.core.metrics);
+    }
+
+    /// Returns a [`QueueMut`] giving exclusive access to the [`RawQueue`], see
+    /// [`Queue::get_mut`].
+    ///
+    /// [`Queue::get_mut`]: crate::queue::Queue::get_mut
+    pub fn get_mut(&mut self) -> QueueMut<'_, T, N, W, A> {
+        QueueMut::new(self)
+    }
+
+    /// Installs the first node of the queue, unless another producer did it first.
+    /// Returns the node the tail cursor points to.
+    fn install_first_node(&self) -> *mut CachePad<Node<T, N>> {
+        let node = self.pool.alloc(&self.core.metrics);
+        match self.core.tail.node.compare_exchange(
+            ptr::null_mut(),
+            node,
+            Ordering::SeqCst,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => {
+                self.core.head.node.store(node, Ordering::SeqCst);
+                node
+            }
+            Err(current) => {
+                unsafe { self.pool.recycle(node, &self.core.metrics) };
+                current
+            }
+        }
+    }
+
+    /// Push an item into the [`RawQueue`], see [`Queue::push`].
+    ///
+    /// [`Queue::push`]: crate::queue::Queue::push
+    pub fn push(&self, item: T) {
+        let _ = self.try_push(item);
+    }
+
+    /// Push an item into the [`RawQueue`], unless it's closed, see
+    /// [`Queue::try_push`].
+    ///
+    /// [`Queue::try_push`]: crate::queue::Queue::try_push
+    pub fn try_push(&self, item: T) -> Result<(), PushError<T>> {
+        let mut tail_index = self.core.tail.index.load(Ordering::Acquire);
+        let mut tail_node = self.core.tail.node.load(Ordering::Acquire);
+        let mut step = 0;
+
+        loop {
+            // Once the queue is closed, no more items can be pushed. As the closed bit
+            // is part of the tail index, closing the queue makes the exchange below fail.
+            if tail_index & CLOSED_BIT != 0 {
+                return Err(PushError::Closed(item));
+            }
+
+            // Defines the node container offset of the slot where the provided item should be stored.
+            let offset = (tail_index >> MARK_BIT_SHIFT) % Self::NODE_SIZE;
+
+            // If the node container is full, we wait until the next node is
+            // installed before moving forward and update our local reference.
+            if offset == Self::NODE_CAPACITY {
+                W::wait(step);
+                step = step.saturating_add(1);
+                self.core.metrics.record(Event::Wait, 1);
+                tail_index = self.core.tail.index.load(Ordering::Acquire);
+                tail_node = self.core.tail.node.load(Ordering::Acquire);
+                continue;
+            }
+
+            // The first push installs the first node.
+            if tail_node.is_null() {
+                tail_node = self.install_first_node();
+            }
+
+            // Increments the tail index.
+            let next_tail_index = tail_index + (1 << MARK_BIT_SHIFT);
+            match self.core.tail.index.compare_exchange_weak(
+                tail_index,
+                next_tail_index,
+                Ordering::SeqCst,
+                Ordering::Acquire,
+            ) {
+                // The tail index has been updated successfully so we can now use
+                // the offset to store the item in the next available slot.
+                Ok(_) => unsafe {
+                    // If we're filling the last available slot of the node container,
+                    // we install a new one and update both the tail and the node to point
+                    // to this new node.
+                    if offset + 1 == Self::NODE_CAPACITY {
+                        let next_node = self.pool.alloc(&self.core.metrics);
+                        self.core.tail.node.store(next_node, Ordering::Release);
+                        let _ = self
+                            .core
+                            .tail
+                            .index
+                            .fetch_add(1 << MARK_BIT_SHIFT, Ordering::Release);
+                        (&*tail_node).next.store(next_node, Ordering::Release);
+                    }
+
+                    // We can now safely store the provided item into the slot.
+                    let slot = (&*tail_node).container.get_unchecked(offset);
+                    slot.item.with_mut(|p| p.write(MaybeUninit::new(item)));
+                    let _ = slot.state.fetch_or(FILLED, Ordering::Release);
+
+                    // Wakes up a consumer waiting for an item, if any.
+                    self.waiters.notify_one();
+
+                    self.core.metrics.record(Event::Push, 1);
+                    return Ok(());
+                },
+                // While trying to push the next item, the tail index
+                // has been updated by another thread. We update our local
+                // references with the value stored when we tried to make
+                // the exchange and what is now the current tail's node.
+                Err(current_tail_index) => {
+                    self.core.metrics.record(Event::TailCasFailure, 1);
+                    tail_index = current_tail_index;
+                    tail_node = self.core.tail.node.load(Ordering::Acquire);
+                }
+            }
+        }
+    }
+
+    /// Push a batch of items into the [`RawQueue`], see [`Queue::push_batch`].
+    ///
+    /// Reserves a run of slots of the tail node with a single exchange for as many
+    /// items as the node can hold. Drops the remaining items once the queue is closed.
+    ///
+    /// [`Queue::push_batch`]: crate::queue::Queue::push_batch
+    pub fn push_batch<I: IntoIterator<Item = T>>(&self, items: I) {
+        let mut items = items.into_iter();
+
+        // Buffers the items to push, as the number of slots to reserve has to be known
+        // before claiming them. A batch never exceeds the capacity of a node.
+        let mut batch = Vec::with_capacity(items.size_hint().0.min(Self::NODE_CAPACITY));
+
+        let mut tail_index = self.core.tail.index.load(Ordering::Acquire);
+        let mut tail_node = self.core.tail.node.load(Ordering::Acquire);
+        let mut step = 0;
+
+        loop {
+            if batch.is_empty() {
+                batch.extend(items.by_ref().take(Self::NODE_CAPACITY));
+                if batch.is_empty() {
+                    return;
+                }
+            }
+
+            if tail_index & CLOSED_BIT != 0 {
+                return;
+            }
+
+            let offset = (tail_index >> MARK_BIT_SHIFT) % Self::NODE_SIZE;
+
+            if offset == Self::NODE_CAPACITY {
+                W::wait(step);
+                step = step.saturating_add(1);
+                self.core.metrics.record(Event::Wait, 1);
+                tail_index = self.core.tail.index.load(Ordering::Acquire);
+                tail_node = self.core.tail.node.load(Ordering::Acquire);
+                continue;
+            }
+
+            // The first push installs the first node.
+            if tail_node.is_null() {
+                tail_node = self.install_first_node();
+            }
+
+            // Reserves as many slots as possible in the tail node, the items left are
+            // pushed into the next one.
+            let count = batch.len().min(Self::NODE_CAPACITY - offset);
+            let next_tail_index = tail_index + (count << MARK_BIT_SHIFT);
+            match self.core.tail.index.compare_exchange_weak(
+                tail_index,
+                next_tail_index,
+                Ordering::SeqCst,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => unsafe {
+                    // If we've reserved the last available slot of the node container,
+                    // we install the next node just like a single push does.
+                    if offset + count == Self::NODE_CAPACITY {
+                        let next_node = self.pool.alloc(&self.core.metrics);
+                        self.core.tail.node.store(next_node, Ordering::Release);
+                        let _ = self
+                            .core
+                            .tail
+                            .index
+                            .fetch_add(1 << MARK_BIT_SHIFT, Ordering::Release);
+                        (&*tail_node).next.store(next_node, Ordering::Release);
+                    }
+
+                    // Each item is made available as soon as it's written, without
+                    // waiting for the whole run to be filled.
+                    for (i, item) in batch.drain(..count).enumerate() {
+                        let slot = (&*tail_node).container.get_unchecked(offset + i);
+                        slot.item.with_mut(|p| p.write(MaybeUninit::new(item)));
+                        let _ = slot.state.fetch_or(FILLED, Ordering::Release);
+
+                        self.waiters.notify_one();
+                    }
+
+                    self.core.metrics.record(Event::Push, count as u64);
+                    tail_index = self.core.tail.index.load(Ordering::Acquire);
+                    tail_node = self.core.tail.node.load(Ordering::Acquire);
+                },
+                Err(current_tail_index) => {
+                    self.core.metrics.record(Event::TailCasFailure, 1);
+                    tail_index = current_tail_index;
+                    tail_node = self.core.tail.node.load(Ordering::Acquire);
+                }
+            }
+        }
+    }
+
+    /// Pop an item from the [`RawQueue`], see [`Queue::pop`].
+    ///
+    /// [`Queue::pop`]: crate::queue::Queue::pop
+    pub fn pop(&self) -> Option<T> {
+        self.pop_result().ok()
+    }
+
+    /// Pops an item, waiting for its producer to finish writing it if needed.
+    ///
+    /// Returns either [`TryPopError::Empty`] or [`TryPopError::Closed`] if there is no item.
+    pub(crate) fn pop_result(&self) -> Result<T, TryPopError> {
+        let mut head_index = self.core.head.index.load(Ordering::Acquire);
+        let mut head_node = self.core.head.node.load(Ordering::Acquire);
+        let mut step = 0;
+
+        loop {
+            // Defines the offset of the slot from where the next item should gathered.
+            let offset = (head_index >> MARK_BIT_SHIFT) % Self::NODE_SIZE;
+
+            // If we reach the end of the node container, we wait until the next
+            // one is installed.
+            if offset == Self::NODE_CAPACITY {
+                W::wait(step);
+                step = step.saturating_add(1);
+                self.core.metrics.record(Event::Wait, 1);
+                head_index = self.core.head.index.load(Ordering::Acquire);
+                head_node = self.core.head.node.load(Ordering::Acquire);
+                continue;
+            }
+
+            // Increments the head index.
+            let mut next_head_index = head_index + (1 << MARK_BIT_SHIFT);
+
+            // If the mark bit is not set in the head index, we check if
+            // there is a pending item in the queue.
+            if next_head_index & MARK_BIT == 0 {
+                // Sync all threads and loads the current tail cursor.
+                fence(Ordering::SeqCst);
+                let tail_index = self.core.tail.index.load(Ordering::Acquire);
+
+                // If the head index equals the tail index, the queue is empty.
+                if head_index >> MARK_BIT_SHIFT == tail_index >> MARK_BIT_SHIFT {
+                    self.core.metrics.record(Event::EmptyPop, 1);
+                    return Err(empty_or_closed(tail_index));
+                }
+
+                // If the head and the tail are not pointing to the same node,
+                // we set the `MARK_BIT` in the head to skip cheking if there
+                // is any item pending on the next iteration.
+                if (head_index >> MARK_BIT_SHIFT) / Self::NODE_SIZE
+                    != (tail_index >> MARK_BIT_SHIFT) / Self::NODE_SIZE
+                {
+                    next_head_index |= MARK_BIT;
+                }
+            }
+
+            // The first node is being installed by the producer of the first item.
+            if head_node.is_null() {
+                W::wait(step);
+                step = step.saturating_add(1);
+                self.core.metrics.record(Event::Wait, 1);
+                head_index = self.core.head.index.load(Ordering::Acquire);
+                head_node = self.core.head.node.load(Ordering::Acquire);
+                continue;
+            }
+
+            // Try update the head index.
+            match self.core.head.index.compare_exchange_weak(
+                head_index,
+                next_head_index,
+                Ordering::SeqCst,
+                Ordering::Acquire,
+            ) {
+                // The head index has been updated successfully so we can now use
+                // the offset to pop the next item.
+                Ok(_) => unsafe {
+                    // If we're returning the last item of the node container, we
+                    // update the head cursor to point to the next node.
+                    if offset + 1 == Self::NODE_CAPACITY {
+                        self.advance_head(head_node, next_head_index);
+                    }
+
+                    // Reads and returns the item.
+                    let slot = (&*head_node).container.get_unchecked(offset);
+                    let waits = slot.wait_filled::<W>();
+                    self.core.metrics.record(Event::Wait, u64::from(waits));
+
+                    self.core.metrics.record(Event::Pop, 1);
+                    return Ok(self.read(head_node, offset));
+                },
+                // While trying to pop the next item, the head index
+                // has been updated by another thread. We update our local
+                // references with the value stored when we tried to make
+                // the exchange and what is now the current head's node.
+                Err(current_head_index) => {
+                    self.core.metrics.record(Event::HeadCasFailure, 1);
+                    head_index = current_head_index;
+                    head_node = self.core.head.node.load(Ordering::Acquire);
+                }
+            }
+        }
+    }
+
+    /// Pop up to `max` items from the [`RawQueue`] into `buf`, see
+    /// [`Queue::pop_batch`].
+    ///
+    /// Claims the items of each node with a single exchange. Returns the number of
+    /// popped items.
+    ///
+    /// [`Queue::pop_batch`]: crate::queue::Queue::pop_batch
+    pub fn pop_batch(&self, buf: &mut Vec<T>, max: usize) -> usize {
+        self.pop_batch_map(buf, max, |item| item)
+    }
+
+    /// Pop up to `max` items from the [`RawQueue`] into `buf`, mapping each one with
+    /// `f`, see [`RawQueue::pop_batch`].
+    ///
+    /// Items are mapped as they're read from their claimed slots, so `f` must not
+    /// panic.
+    pub(crate) fn pop_batch_map<U>(
+        &self,
+        buf: &mut Vec<U>,
+        max: usize,
+        mut f: impl FnMut(T) -> U,
+    ) -> usize {
+        let mut popped = 0;
+        let mut head_index = self.core.head.index.load(Ordering::Acquire);
+        let mut head_node = self.core.head.node.load(Ordering::Acquire);
+        let mut step = 0;
+
+        while popped < max {
+            let offset = (head_index >> MARK_BIT_SHIFT) % Self::NODE_SIZE;
+
+            if offset == Self::NODE_CAPACITY {
+                W::wait(step);
+                step = step.saturating_add(1);
+                self.core.metrics.record(Event::Wait, 1);
+                head_index = self.core.head.index.load(Ordering::Acquire);
+                head_node = self.core.head.node.load(Ordering::Acquire);
+                continue;
+            }
+
+            // If the mark bit is set, the next node has been installed, so every slot
+            // left in the head node has been claimed by a producer. Otherwise, only the
+            // slots up to the tail have.
+            let mut available = Self::NODE_CAPACITY - offset;
+            let mut next_head_index = head_index;
+            if head_index & MARK_BIT == 0 {
+                // Sync all threads and loads the current tail cursor.
+                fence(Ordering::SeqCst);
+                let tail_index = self.core.tail.index.load(Ordering::Acquire);
+
+                // If the head index equals the tail index, the queue is empty.
+                if head_index >> MARK_BIT_SHIFT == tail_index >> MARK_BIT_SHIFT {
+                    if popped == 0 {
+                        self.core.metrics.record(Event::EmptyPop, 1);
+                    }
+                    break;
+                }
+
+                if (head_index >> MARK_BIT_SHIFT) / Self::NODE_SIZE
+                    != (tail_index >> MARK_BIT_SHIFT) / Self::NODE_SIZE
+                {
+                    next_head_index |= MARK_BIT;
+                } else {
+                    available = ((tail_index >> MARK_BIT_SHIFT) % Self::NODE_SIZE) - offset;
+                }
+            }
+
+            // The first node is being installed by the producer of the first item.
+            if head_node.is_null() {
+                W::wait(step);
+                step = step.saturating_add(1);
+                self.core.metrics.record(Event::Wait, 1);
+                head_index = self.core.head.index.load(Ordering::Acquire);
+                head_node = self.core.head.node.load(Ordering::Acquire);
+                continue;
+            }
+
+            // Makes room for the items before claiming them, so we can't fail to store
+            // them once claimed.
+            let count = available.min(max - popped);
+            buf.reserve(count);
+            next_head_index += count << MARK_BIT_SHIFT;
+
+            match self.core.head.index.compare_exchange_weak(
+                head_index,
+                next_head_index,
+                Ordering::SeqCst,
+                Ordering::Acquire,
+            ) {
+                // The head index has been updated successfully, so the run of slots is
+                // ours. Each slot is read just like a single pop does, which drains and
+                // releases the node once its last slot has been read.
+                Ok(_) => unsafe {
+                    if offset + count == Self::NODE_CAPACITY {
+                        self.advance_head(head_node, next_head_index);
+                    }
+
+                    for i in offset..offset + count {
+                        let waits = (&*head_node).container.get_unchecked(i).wait_filled::<W>();
+                        self.core.metrics.record(Event::Wait, u64::from(waits));
+                        buf.push(f(self.read(head_node, i)));
+                    }
+
+                    self.core.metrics.record(Event::Pop, count as u64);
+                    popped += count;
+                    head_index = self.core.head.index.load(Ordering::Acquire);
+                    head_node = self.core.head.node.load(Ordering::Acquire);
+                },
+                Err(current_head_index) => {
+                    self.core.metrics.record(Event::HeadCasFailure, 1);
+                    head_index = current_head_index;
+                    head_node = self.core.head.node.load(Ordering::Acquire);
+                }
+            }
+        }
+
+        popped
+    }
+
+    /// Pushes an item with exclusive access to the queue, or gives it back if the queue
+    /// is closed.
+    ///
+    /// No other thread can use the queue, so the cursors are updated with plain stores
+    /// and the tail never has to wait for the next node to be installed.
+    pub(crate) fn push_mut(&mut self, item: T) -> Result<(), T> {
+        let tail_index = self.core.tail.index.load(Ordering::Relaxed);
+        if tail_index & CLOSED_BIT != 0 {
+            return Err(item);
+        }
+
+        let mut tail_node = self.core.tail.node.load(Ordering::Relaxed);
+        if tail_node.is_null() {
+            tail_node = self.install_first_node();
+        }
+
+        let offset = (tail_index >> MARK_BIT_SHIFT) % Self::NODE_SIZE;
+        let mut next_tail_index = tail_index + (1 << MARK_BIT_SHIFT);
+
+        unsafe {
+            let slot = (&*tail_node).container.get_unchecked(offset);
+            slot.item.with_mut(|p| p.write(MaybeUninit::new(item)));
+            slot.state.store(FILLED, Ordering::Relaxed);
+
+            // If we've filled the last slot of the node container, we install the next
+            // node and move the tail to its first slot at once.
+            if offset + 1 == Self::NODE_CAPACITY {
+                let next_node = self.pool.alloc(&self.core.metrics);
+                (&*tail_node).next.store(next_node, Ordering::Relaxed);
+                self.core.tail.node.store(next_node, Ordering::Relaxed);
+                next_tail_index += 1 << MARK_BIT_SHIFT;
+            }
+        }
+
+        self.core
+            .tail
+            .index
+            .store(next_tail_index, Ordering::Relaxed);
+        self.core.metrics.record(Event::Push, 1);
+        Ok(())
+    }
+
+    /// Pops an item with exclusive access to the queue.
+    pub(crate) fn pop_mut(&mut self) -> Option<T> {
+        let head_index = self.core.head.index.load(Ordering::Relaxed);
+        let tail_index = self.core.tail.index.load(Ordering::Relaxed);
+        if head_index >> MARK_BIT_SHIFT == tail_index >> MARK_BIT_SHIFT {
+            self.core.metrics.record(Event::EmptyPop, 1);
+            return None;
+        }
+
+        let head_node = self.core.head.node.load(Ordering::Relaxed);
+        let offset = (head_index >> MARK_BIT_SHIFT) % Self::NODE_SIZE;
+        let mut next_head_index = head_index + (1 << MARK_BIT_SHIFT);
+
+        unsafe {
+            let slot = (&*head_node).container.get_unchecked(offset);
+            let item = slot.item.with(|p| p.read().assume_init());
+
+            if offset + 1 == Self::NODE_CAPACITY {
+                // The next node has been installed before the last slot was filled, so
+                // we move the head to its first slot and release the drained node.
+                // Removing the mark bit only makes the next pop check the tail.
+                let next_node = (&*head_node).next.load(Ordering::Relaxed);
+                self.core.head.node.store(next_node, Ordering::Relaxed);
+                next_head_index = (next_head_index & !MARK_BIT) + (1 << MARK_BIT_SHIFT);
+                self.pool.recycle(head_node, &self.core.metrics);
+            } else {
+                // Reports the slot as read, so that the thread reading the last slot of
+                // the node can drain it once the queue is shared again.
+                slot.state.store(FILLED | READING, Ordering::Relaxed);
+            }
+
+            self.core
+                .head
+                .index
+                .store(next_head_index, Ordering::Relaxed);
+            self.core.metrics.record(Event::Pop, 1);
+            Some(item)
+        }
+    }
+
+    /// Pops an item, blocking the current thread until an item is available, the queue
+    /// is closed, or the `deadline` is reached.
+    #[cfg(feature = "std")]
+    pub(crate) fn pop_until(&self, deadline: Option<Instant>) -> Result<T, PopTimeoutError> {
+        self.waiters
+            .wait_until(deadline, || match self.pop_result() {
+                Ok(item) => Some(Ok(item)),
+                Err(TryPopError::Closed) => Some(Err(PopTimeoutError::Closed)),
+                Err(_) => None,
+            })
+            .unwrap_or(Err(PopTimeoutError::Timeout))
+    }
+
+    /// Pop an item from the [`RawQueue`], blocking the current thread until an item
+    /// is available, see [`Queue::pop_blocking`].
+    ///
+    /// [`Queue::pop_blocking`]: crate::queue::Queue::pop_blocking
+    #[cfg(feature = "std")]
+    pub fn pop_blocking(&self) -> Option<T> {
+        self.pop_until(None).ok()
+    }
+
+    /// Pop an item from the [`RawQueue`], blocking the current thread for at most
+    /// `timeout`, see [`Queue::pop_timeout`].
+    ///
+    /// [`Queue::pop_timeout`]: crate::queue::Queue::pop_timeout
+    #[cfg(feature = "std")]
+    pub fn pop_timeout(&self, timeout: Duration) -> Result<T, PopTimeoutError> {
+        // A deadline too far in the future to be represented is as good as none.
+        self.pop_until(Instant::now().checked_add(timeout))
+    }
+
+    /// Pop an item from the [`RawQueue`], blocking the current thread until the
+    /// `deadline` is reached, see [`Queue::pop_deadline`].
+    ///
+    /// [`Queue::pop_deadline`]: crate::queue::Queue::pop_deadline
+    #[cfg(feature = "std")]
+    pub fn pop_deadline(&self, deadline: Instant) -> Result<T, PopTimeoutError> {
+        self.pop_until(Some(deadline))
+    }
+
+    /// Pop an item from the [`RawQueue`] asynchronously, see [`Queue::pop_async`].
+    ///
+    /// [`Queue::pop_async`]: crate::queue::Queue::pop_async
+    #[cfg(feature = "async")]
+    pub fn pop_async(&self) -> PopFuture<'_, T, N, W, A> {
+        PopFuture::new(self)
+    }
+
+    /// Returns an iterator popping the items of the [`RawQueue`], see
+    /// [`Queue::try_iter`].
+    ///
+    /// [`Queue::try_iter`]: crate::queue::Queue::try_iter
+    pub fn try_iter(&self) -> PopIter<'_, T, N, W, A> {
+        PopIter::new(self)
+    }
+
+    /// Returns an iterator popping the items held by the [`RawQueue`] when called, see
+    /// [`Queue::drain`].
+    ///
+    /// [`Queue::drain`]: crate::queue::Queue::drain
+    pub fn drain(&self) -> Drain<'_, T, N, W, A> {
+        Drain::new(self, self.len())
+    }
+
+    /// Closes the [`RawQueue`], see [`Queue::close`].
+    ///
+    /// [`Queue::close`]: crate::queue::Queue::close
+    pub fn close(&self) -> bool {
+        let tail_index = self.core.tail.index.fetch_or(CLOSED_BIT, Ordering::SeqCst);
+        if tail_index & CLOSED_BIT != 0 {
+            return false;
+        }
+
+        // Wakes up every consumer, so they can report that the queue is closed once
+        // they've popped the remaining items.
+        self.waiters.notify_all();
+        true
+    }
+
+    /// Returns `true` if the [`RawQueue`] is closed.
+    pub fn is_closed(&self) -> bool {
+        self.core.tail.index.load(Ordering::SeqCst) & CLOSED_BIT != 0
+    }
+
+    /// Returns `true` if the [`RawQueue`] is empty.
+    pub fn is_empty(&self) -> bool {
+        let head_index = self.core.head.index.load(Ordering::SeqCst);
+        let tail_index = self.core.tail.index.load(Ordering::SeqCst);
+        head_index >> MARK_BIT_SHIFT == tail_index >> MARK_BIT_SHIFT
+    }
+
+    /// Returns a snapshot of the operations counted by the [`RawQueue`], see
+    /// [`Queue::stats`].
+    ///
+    /// [`Queue::stats`]: crate::queue::Queue::stats
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> QueueStats {
+        self.core.stats()
+    }
+
+    /// Try to pop an item from the [`RawQueue`] without waiting for any other thread,
+    /// see [`Queue::try_pop`].
+    ///
+    /// [`Queue::try_pop`]: crate::queue::Queue::try_pop
+    pub fn try_pop(&self) -> Result<T, TryPopError> {
+        // Registers the thread as a peeker only once it has to look into the head node,
+        // so that finding the queue empty or busy doesn't touch the shared counter.
+        let mut guard = None;
+
+        let mut head_index = self.core.head.index.load(Ordering::Acquire);
+        let mut head_node = self.core.head.node.load(Ordering::Acquire);
+
+        loop {
+            // Defines the offset of the slot from where the next item should gathered.
+            let offset = (head_index >> MARK_BIT_SHIFT) % Self::NODE_SIZE;
+
+            // If we reach the end of the node container, another consumer is
+            // installing the next one.
+            if offset == Self::NODE_CAPACITY {
+                return Err(TryPopError::Busy);
+            }
+
+            // Increments the head index.
+            let mut next_head_index = head_index + (1 << MARK_BIT_SHIFT);
+
+            // If the mark bit is not set in the head index, we check if
+            // there is a pending item in the queue.
+            if next_head_index & MARK_BIT == 0 {
+                // Sync all threads and loads the current tail cursor.
+                fence(Ordering::SeqCst);
+                let tail_index = self.core.tail.index.load(Ordering::Acquire);
+
+                // If the head index equals the tail index, the queue is empty.
+                if head_index >> MARK_BIT_SHIFT == tail_index >> MARK_BIT_SHIFT {
+                    self.core.metrics.record(Event::EmptyPop, 1);
+                    return Err(empty_or_closed(tail_index));
+                }
+
+                // If the head and the tail are not pointing to the same node,
+                // we set the `MARK_BIT` in the head to skip cheking if there
+                // is any item pending on the next iteration.
+                if (head_index >> MARK_BIT_SHIFT) / Self::NODE_SIZE
+                    != (tail_index >> MARK_BIT_SHIFT) / Self::NODE_SIZE
+                {
+                    next_head_index |= MARK_BIT;
+                }
+            }
+
+            // The first node is being installed by the producer of the first item.
+            if head_node.is_null() {
+                return Err(TryPopError::Busy);
+            }
+
+            // Prevents the nodes we look into from being freed until we're done. The
+            // head node we loaded may have been freed before we registered, so we load
+            // the head cursor again.
+            if guard.is_none() {
+                guard = Some(PeekGuard::new(self));
+                head_index = self.core.head.index.load(Ordering::Acquire);
+                head_node = self.core.head.node.load(Ordering::Acquire);
+                continue;
+            }
+
+            // Before claiming the slot, we make sure its producer has finished
+            // writing the item. The node can't be freed while we're peeking.
+            let slot = unsafe { (&*head_node).container.get_unchecked(offset) };
+            if slot.state.load(Ordering::Acquire) & FILLED == 0 {
+                // The head node we loaded matches the head index only if the
+                // index didn't move in the meantime.
+                let current_head_index = self.core.head.index.load(Ordering::Acquire);
+                if current_head_index == head_index {
+                    return Err(TryPopError::Busy);
+                }
+
+                head_index = current_head_index;
+                head_node = self.core.head.node.load(Ordering::Acquire);
+                continue;
+            }
+
+            // Try update the head index.
+            match self.core.head.index.compare_exchange_weak(
+                head_index,
+                next_head_index,
+                Ordering::SeqCst,
+                Ordering::Acquire,
+            ) {
+                // The head index has been updated successfully, so the slot we
+                // looked into is ours and already filled.
+                Ok(_) => unsafe {
+                    // The producer of the last item of the node container installs
+                    // the next node before filling the slot, so it's already there.
+                    if offset + 1 == Self::NODE_CAPACITY {
+                        self.advance_head(head_node, next_head_index);
+                    }
+
+                    self.core.metrics.record(Event::Pop, 1);
+                    return Ok(self.read(head_node, offset));
+                },
+                // While trying to pop the next item, the head index
+                // has been updated by another thread. We update our local
+                // references with the value stored when we tried to make
+                // the exchange and what is now the current head's node.
+                Err(current_head_index) => {
+                    self.core.metrics.record(Event::HeadCasFailure, 1);
+                    head_index = current_head_index;
+                    head_node = self.core.head.node.load(Ordering::Acquire);
+                }
+            }
+        }
+    }
+
+    /// Updates the head cursor to point to the node following `head_node`, once
+    /// the last slot of `head_node` has been claimed.
+    unsafe fn advance_head(&self, head_node: *mut CachePad<Node<T, N>>, next_head_index: usize) {
+        let (next_node, waits) = unsafe { (&*head_node).wait_next::<W>() };
+        self.core.metrics.record(Event::Wait, u64::from(waits));
+
+        // Remove the mark bit if any and increment the index.
+        let mut next_index = (next_head_index & !MARK_BIT).wrapping_add(1 << MARK_BIT_SHIFT);
+
+        // If the next node points to another node, we can already
+        // update the index to report that the next node that will
+        // be installed is not the last one.
+        if !unsafe { (&*next_node).next.load(Ordering::Relaxed) }.is_null() {
+            next_index |= MARK_BIT;
+        }
+
+        self.core.head.node.store(next_node, Ordering::Release);
+        self.core.head.index.store(next_index, Ordering::Release);
+    }
+
+    /// Reads the item of a claimed and filled slot.
+    unsafe fn read(&self, head_node: *mut CachePad<Node<T, N>>, offset: usize) -> T {
+        let slot = unsafe { (&*head_node).container.get_unchecked(offset) };
+        let item = slot.item.with(|p| unsafe { p.read().assume_init() });
+
+        // Drain and release the node if we've reached the end of its container, or if another
+        // thread wanted to do so but couldn't because this thread was busy reading from the slot.
+        if offset + 1 == Self::NODE_CAPACITY {
+            if unsafe { Node::drain(head_node, 0) } {
+                unsafe { self.release(head_node) };
+            }
+        } else if slot.state.fetch_or(READING, Ordering::AcqRel) & DRAINING != 0
+            && unsafe { Node::drain(head_node, offset + 1) }
+        {
+            unsafe { self.release(head_node) };
+        }
+
+        item
+    }
+
+    /// Recycles a drained node, or retires it if a thread might still be peeking into it.
+    unsafe fn release(&self, node: *mut CachePad<Node<T, N>>) {
+        fence(Ordering::SeqCst);
+        if self.peekers.load(Ordering::SeqCst) == 0 {
+            unsafe { self.pool.recycle(node, &self.core.metrics) };
+        } else {
+            unsafe { self.retire(node, node) };
+        }
+    }
+}
+
+/// Registers a thread peeking into the head [`Node`] until dropped.
+///
+/// When the last peeker leaves, it frees the nodes retired in the meantime.
+struct PeekGuard<'a, T, const N: usize, W, A: NodeAllocator> {
+    inner: &'a RawQueue<T, N, W, A>,
+}
+
+impl<'a, T, const N: usize, W, A: NodeAllocator> PeekGuard<'a, T, N, W, A> {
+    fn new(inner: &'a RawQueue<T, N, W, A>) -> Self {
+        let _ = inner.peekers.fetch_add(1, Ordering::SeqCst);
+        fence(Ordering::SeqCst);
+        Self { inner }
+    }
+}
+
+impl<T, const N: usize, W, A: NodeAllocator> Drop for PeekGuard<'_, T, N, W, A> {
+    fn drop(&mut self) {
+        if self.inner.peekers.fetch_sub(1, Ordering::SeqCst) != 1 {
+            return;
+        }
+
+        let first = self.inner.retired.swap(ptr::null_mut(), Ordering::AcqRel);
+        if first.is_null() {
+            return;
+        }
+
+        // Retired nodes are no longer reachable from the head cursor, so only
+        // the threads that started peeking before they were retired can use them.
+        fence(Ordering::SeqCst);
+        if self.inner.peekers.load(Ordering::SeqCst) == 0 {
+            unsafe { self.inner.pool.free_chain(first, &self.inner.core.metrics) };
+        } else {
+            let mut last = first;
+            loop {
+                let next = unsafe { (&*last).next.load(Ordering::Relaxed) };
+                if next.is_null() {
+                    break;
+                }
+                last = next;
+            }
+            unsafe { self.inner.retire(first, last) };
+        }
+    }
+}
+
+/// Reports why there is no item to pop, given the current tail index.
+fn empty_or_closed(tail_index: usize) -> TryPopError {
+    if tail_index & CLOSED_BIT != 0 {
+        TryPopError::Closed
+    } else {
+        TryPopError::Empty
+    }
+}
+
+/// Formats the state of a queue as a struct named `name`.
+struct State<'a, T, const N: usize, W, A: NodeAllocator> {
+    queue: &'a RawQueue<T, N, W, A>,
+    name: &'a str,
+}
+
+impl<T, const N: usize, W, A: NodeAllocator> fmt::Debug for State<'_, T, N, W, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.queue.fmt_state(self.name, f)
+    }
+}
+
+impl<T, const N: usize, W, A: NodeAllocator> fmt::Debug for RawQueue<T, N, W, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_state("RawQueue", f)
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy, A: NodeAllocator + Default> Default
+    for RawQueue<T, N, W, A>
+{
+    fn default() -> Self {
+        Self::new_in(A::default())
+    }
+}
+
+impl<T, const N: usize, W, A: NodeAllocator> Drop for RawQueue<T, N, W, A> {
+    fn drop(&mut self) {
+        // Having a mutable reference means no other thread can access the queue
+        // anymore, so every push and pop has completed and all the slots between
+        // the head and the tail cursors hold an item.
+        let mut head_index = self.core.head.index.load(Ordering::Relaxed) & !MARK_BIT;
+        let tail_index = self.core.tail.index.load(Ordering::Relaxed) & !CLOSED_BIT;
+        let mut head_node = self.core.head.node.load(Ordering::Relaxed);
+
+        unsafe {
+            while head_index != tail_index {
+                let offset = (head_index >> MARK_BIT_SHIFT) % Self::NODE_SIZE;
+
+                if offset < Self::NODE_CAPACITY {
+                    // Drops the item still stored in the slot.
+                    let slot = (&*head_node).container.get_unchecked(offset);
+                    if slot.state.load(Ordering::Relaxed) & FILLED != 0 {
+                        slot.item
+                            .with_mut(|p| ptr::drop_in_place((*p).as_mut_ptr()));
+                    }
+                } else {
+                    // We've reached the end of the node container, we can move
+                    // to the next one and free the current one.
+                    let next_node = (&*head_node).next.load(Ordering::Relaxed);
+                    self.pool.free(head_node, &self.core.metrics);
+                    head_node = next_node;
+                }
+
+                head_index = head_index.wrapping_add(1 << MARK_BIT_SHIFT);
+            }
+
+            // Frees the last node, the one both cursors point to.
+            if !head_node.is_null() {
+                self.pool.free(head_node, &self.core.metrics);
+            }
+
+            self.pool
+                .free_chain(self.retired.load(Ordering::Relaxed), &self.core.metrics);
+        }
+
+        // Frees the drained nodes kept for reuse.
+        self.pool.clear(&self.core.metrics);
+    }
+}
+
+/// Holds the cursors and the counters of a queue, which is all the registry needs to
+/// report its state.
+struct Core<T, const N: usize> {
+    head: CachePad<Cursor<T, N>>,
+    tail: CachePad<Cursor<T, N>>,
+
+    /// Counts the operations of the queue.
+    metrics: Metrics,
+}
+
+impl<T, const N: usize> Core<T, N> {
+    /// Reports the number of indices covered by a [`Node`].
+    const NODE_SIZE: usize = Node::<T, N>::SIZE;
+
+    /// Reports the capacity (max number of item), a [`Node`] container can hold.
+    const NODE_CAPACITY: usize = Node::<T, N>::CAPACITY;
+
+    #[cfg(not(loom))]
+    const fn new() -> Self {
+        Self {
+            head: CachePad::new(Cursor::new()),
+            tail: CachePad::new(Cursor::new()),
+            metrics: Metrics::new(),
+        }
+    }
+
+    // In loom, atomics can't be created in a `const fn`.
+    #[cfg(loom)]
+    fn new() -> Self {
+        Self {
+            head: CachePad::new(Cursor::new()),
+            tail: CachePad::new(Cursor::new()),
+            metrics: Metrics::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        loop {
+            // Loads the tail index twice to make sure the head index we load is
+            // consistent with it, i.e. they've both been loaded at the same time.
+            let tail_index = self.tail.index.load(Ordering::SeqCst);
+            let head_index = self.head.index.load(Ordering::SeqCst);
+            if self.tail.index.load(Ordering::SeqCst) != tail_index {
+                continue;
+            }
+
+            // Removes the bits holding metadata.
+            let mut tail = (tail_index & !CLOSED_BIT) >> MARK_BIT_SHIFT;
+            let mut head = (head_index & !MARK_BIT) >> MARK_BIT_SHIFT;
+
+            // An index pointing to the extra index of a node, reached while the next node
+            // is being installed, is moved to the first slot of the next node.
+            if tail % Self::NODE_SIZE == Self::NODE_CAPACITY {
+                tail = tail.wrapping_add(1);
+            }
+            if head % Self::NODE_SIZE == Self::NODE_CAPACITY {
+                head = head.wrapping_add(1);
+            }
+
+            // Moves both indices so that the head falls into the first node, then
+            // removes the extra index of each node between the head and the tail.
+            let offset = head - head % Self::NODE_SIZE;
+            let tail = tail.wrapping_sub(offset);
+            let head = head.wrapping_sub(offset);
+
+            return tail - head - tail / Self::NODE_SIZE;
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn stats(&self) -> QueueStats {
+        let mut stats = self.metrics.stats();
+        stats.len = self.len();
+        stats
+    }
+}
+
+// The registry only reads the indices and the counters, never the nodes.
+#[cfg(all(feature = "registry", not(loom)))]
+impl<T, const N: usize> Introspect for Core<T, N> {
+    fn stats(&self) -> QueueStats {
+        Core::stats(self)
+    }
+}
+
+/// Holds the [`Core`] of a queue, in its own allocation if the queue is named, so
+/// that the registry can keep a weak reference to it without holding any handle of
+/// the queue.
+///
+/// The [`Core`] of the other queues stays inline, so that they can be created in a
+/// `const` context and don't go through an extra indirection.
+#[cfg(all(feature = "registry", not(loom)))]
+#[allow(clippy::large_enum_variant)]
+enum CoreRef<T, const N: usize> {
+    Inline(Core<T, N>),
+    Shared(Arc<Core<T, N>>),
+}
+
+#[cfg(all(feature = "registry", not(loom)))]
+impl<T, const N: usize> CoreRef<T, N> {
+    const fn new(core: Core<T, N>) -> Self {
+        Self::Inline(core)
+    }
+}
+
+#[cfg(all(feature = "registry", not(loom)))]
+impl<T, const N: usize> Deref for CoreRef<T, N> {
+    type Target = Core<T, N>;
+
+    #[inline]
+    fn deref(&self) -> &Core<T, N> {
+        match self {
+            Self::Inline(core) => core,
+            Self::Shared(core) => core,
+        }
+    }
+}
+
+/// Holds the [`Core`] of a queue.
+#[cfg(not(all(feature = "registry", not(loom))))]
+struct CoreRef<T, const N: usize>(Core<T, N>);
+
+#[cfg(not(all(feature = "registry", not(loom))))]
+impl<T, const N: usize> CoreRef<T, N> {
+    const fn new(core: Core<T, N>) -> Self {
+        Self(core)
+    }
+}
+
+#[cfg(not(all(feature = "registry", not(loom))))]
+impl<T, const N: usize> Deref for CoreRef<T, N> {
+    type Target = Core<T, N>;
+
+    #[inline]
+    fn deref(&self) -> &Core<T, N> {
+        &self.0
+    }
+}
+
+struct Cursor<T, const N: usize> {
+    /// Reports the index of the next [`Slot`].
+    ///
+    /// Its value is used to define the offset of the slot into the current
+    /// [`Node`] container by divinding it by the [`Node::SIZE`].
+    ///
+    /// [`Slot`]: crate::slot::Slot
+    index: AtomicUsize,
+
+    /// Points to the current [`Node`].
+    node: AtomicPtr<CachePad<Node<T, N>>>,
+}
+
+impl<T, const N: usize> Cursor<T, N> {
+    #[cfg(not(loom))]
+    const fn new() -> Self {
+        Self {
+            index: AtomicUsize::new(0),
+            node: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    // In loom, atomics can't be created in a `const fn`.
+    #[cfg(loom)]
+    fn new() -> Self {
+        Self {
+            index: AtomicUsize::new(0),
+            node: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+}
+
+/// Defines how many lower bits are reserved for metadata.
+const MARK_BIT_SHIFT: usize = 1;
+
+/// The [`MARK_BIT`] indicates that the [`Node`] is not the last one.
+///
+/// The [`MARK_BIT`] helps to avoid loading the tail and head simultaneously
+/// to check whether or not the queue is empty when calling the `pop` method.
+///
+/// [`Node`]: crate::node::Node
+const MARK_BIT: usize = 1;
+
+/// The [`CLOSED_BIT`] indicates that the [`Queue`] is closed.
+///
+/// The [`MARK_BIT`] is only used by the head index, so the tail index uses the same
+/// bit to report whether or not the [`Queue`] is closed. Checking it when updating
+/// the tail index ensures that no item can be pushed once the [`Queue`] is closed.
+const CLOSED_BIT: usize = 1;
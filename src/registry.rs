@@ -0,0 +1,155 @@
+//! A process-wide registry of named queues, to find out which one is backing up.
+//!
+//! A [`Queue`] created with [`Queue::named`] registers itself into the registry, which
+//! only holds a weak reference to the cursors and the counters of the [`Queue`], so it
+//! doesn't prevent exclusive access to the [`Queue`], e.g. through [`Queue::get_mut`].
+//! Its entry is removed once the last handle of the [`Queue`] is dropped, so
+//! [`snapshot`] only reports the queues still alive.
+//!
+//! This module requires the `registry` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use lf_queue::{registry, Queue};
+//!
+//! let ingest = Queue::<usize>::named("ingest");
+//! ingest.push_batch(0..3);
+//!
+//! let snapshot = registry::snapshot();
+//! let queue = snapshot.iter().find(|q| q.name == "ingest").unwrap();
+//! assert_eq!(queue.stats.len, 3);
+//! assert_eq!(queue.stats.pushes, 3);
+//!
+//! drop(ingest);
+//! assert!(registry::snapshot().iter().all(|q| q.name != "ingest"));
+//! ```
+//!
+//! [`Queue`]: crate::queue::Queue
+//! [`Queue::get_mut`]: crate::queue::Queue::get_mut
+//! [`Queue::named`]: crate::queue::Queue::named
+
+use crate::metrics::{self, QueueStats};
+use crate::variant::sync::Mutex;
+
+use alloc::string::String;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Holds the registered queues, in the order they've been registered.
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    next_id: 0,
+    entries: Vec::new(),
+});
+
+#[derive(Debug)]
+struct Registry {
+    /// Identifies the next registered queue.
+    next_id: u64,
+
+    /// Reports the registered queues.
+    entries: Vec<Entry>,
+}
+
+/// A registered queue.
+struct Entry {
+    id: u64,
+    name: String,
+    queue: Weak<dyn Introspect>,
+}
+
+impl fmt::Debug for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Entry")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Reports the statistics of a queue, whatever the type of its items.
+pub(crate) trait Introspect: Send + Sync {
+    fn stats(&self) -> QueueStats;
+}
+
+/// Removes the entry of a queue from the registry when dropped.
+///
+/// The queue owns its [`Registration`], so the entry is removed once the last
+/// handle of the queue is dropped.
+#[derive(Debug)]
+pub(crate) struct Registration {
+    id: u64,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let mut registry = REGISTRY.lock().unwrap();
+        if let Some(i) = registry.entries.iter().position(|e| e.id == self.id) {
+            let _ = registry.entries.remove(i);
+        }
+    }
+}
+
+/// Adds a queue to the registry.
+pub(crate) fn register(name: String, queue: Weak<dyn Introspect>) -> Registration {
+    let mut registry = REGISTRY.lock().unwrap();
+    let id = registry.next_id;
+    registry.next_id += 1;
+    registry.entries.push(Entry { id, name, queue });
+    Registration { id }
+}
+
+/// Reports the state of a registered queue when [`snapshot`] was called.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct QueueSnapshot {
+    /// The name the queue has been registered with. Several queues may share the
+    /// same name.
+    pub name: String,
+
+    /// The statistics of the queue, including its approximate length.
+    pub stats: QueueStats,
+}
+
+/// Returns the state of every live registered queue, in the order they've been
+/// registered.
+pub fn snapshot() -> Vec<QueueSnapshot> {
+    // The queues are upgraded while holding the lock, but their statistics are read
+    // once it's released, so that summing up their counters doesn't hold up the
+    // queues being created or dropped.
+    let queues: Vec<(String, Arc<dyn Introspect>)> = REGISTRY
+        .lock()
+        .unwrap()
+        .entries
+        .iter()
+        .filter_map(|e| Some((e.name.clone(), e.queue.upgrade()?)))
+        .collect();
+
+    queues
+        .into_iter()
+        .map(|(name, queue)| QueueSnapshot {
+            stats: queue.stats(),
+            name,
+        })
+        .collect()
+}
+
+/// Writes the statistics of every live registered queue in the [OpenMetrics] text
+/// format, labeling each sample with the name of its queue.
+///
+/// The output is a complete exposition, terminated by an `# EOF` line, see
+/// [`QueueStats::write_openmetrics`]. Queues sharing the same name can't be told
+/// apart, so each queue should be given its own name.
+///
+/// [OpenMetrics]: https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md
+pub fn write_openmetrics(w: &mut impl fmt::Write) -> fmt::Result {
+    let snapshot = snapshot();
+    let queues: Vec<(&str, QueueStats)> = snapshot
+        .iter()
+        .map(|q| (q.name.as_str(), q.stats))
+        .collect();
+
+    metrics::write_openmetrics(w, &queues)?;
+    w.write_str("# EOF\n")
+}
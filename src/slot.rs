@@ -19,39 +19,45 @@
 //! READING -> DRAINING 0b00000111
 //! ```
 //!
-//! [`Node`]: crate::node::Node
-//! [`NODE_CAPACITY`]: crate::node::NODE_CAPACITY
+//! The [`BoundedQueue`] reuses its slots around a ring, so their state holds a stamp
+//! instead, see [`bounded`].
+//!
+//! [`BoundedQueue`]: crate::bounded::BoundedQueue
 //! [`Queue`]: crate::queue::Queue
+//! [`bounded`]: crate::bounded
 
 use crate::variant::cell::UnsafeCell;
 use crate::variant::sync::atomic::AtomicUsize;
-use crate::variant::thread;
+use crate::wait::WaitStrategy;
 
-use std::mem::MaybeUninit;
-use std::sync::atomic::Ordering;
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::sync::atomic::Ordering;
 
 /// Holds an item of the [`Queue`].
 ///
 /// [`Queue`]: crate::queue::Queue
-#[derive(Debug)]
 pub(crate) struct Slot<T> {
     /// Holds an item pushed to the [`Queue`].
     ///
     /// [`Queue`]: crate::queue::Queue
     pub(crate) item: UnsafeCell<MaybeUninit<T>>,
 
-    /// Reports the state of the [`Slot`].
+    /// Reports the state of the [`Slot`], or its stamp in a [`BoundedQueue`].
+    ///
+    /// [`BoundedQueue`]: crate::bounded::BoundedQueue
     pub(crate) state: AtomicUsize,
 }
 
 impl<T> Slot<T> {
-    /// When creating a new [`Node`], [`NODE_CAPACITY`] unititialized
+    /// When creating a new [`Node`], [`Node::CAPACITY`] unititialized
     /// [`Slot`] are added to the [`Node`] container. Using a constant
     /// help us reducing the cost of this operation.
     ///
     /// [`Node`]: crate::node::Node
-    /// [`NODE_CAPACITY`]: crate::node::NODE_CAPACITY
+    /// [`Node::CAPACITY`]: crate::node::Node::CAPACITY
     #[cfg(not(loom))]
+    #[allow(clippy::declare_interior_mutable_const)]
     pub(crate) const UNINIT: Slot<T> = Self {
         item: UnsafeCell::new(MaybeUninit::uninit()),
         state: AtomicUsize::new(0),
@@ -69,21 +75,48 @@ impl<T> Slot<T> {
         }
     }
 
+    /// Creates an empty [`Slot`] starting with the given `state`.
+    pub(crate) fn with_state(state: usize) -> Self {
+        Self {
+            item: UnsafeCell::new(MaybeUninit::uninit()),
+            state: AtomicUsize::new(state),
+        }
+    }
+
     /// Waits until the state has a [`FILLED`] state.
-    pub(crate) fn wait_filled(&self) {
+    ///
+    /// Returns the number of times the thread waited.
+    pub(crate) fn wait_filled<W: WaitStrategy>(&self) -> u32 {
+        let mut step = 0;
         while self.state.load(Ordering::Acquire) & FILLED == 0 {
-            thread::yield_now()
+            W::wait(step);
+            step = step.saturating_add(1);
         }
+        step
     }
 }
 
-// As we can't use compile time constants and function to create a new
-// Node container in loom, we implement the Default trait to simplify
-// the creation of a new Node container with uninitialized Slots.
-#[cfg(loom)]
-impl<T> Default for Slot<T> {
-    fn default() -> Self {
-        Self::new()
+// The item may be written or read by another thread, so only the state is reported,
+// e.g. `FILLED|READING`, or `EMPTY` if no bit flag has been added yet.
+impl<T> fmt::Debug for Slot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.load(Ordering::Acquire);
+        if state == 0 {
+            return f.write_str("EMPTY");
+        }
+
+        let mut separator = "";
+        for (flag, name) in [
+            (FILLED, "FILLED"),
+            (READING, "READING"),
+            (DRAINING, "DRAINING"),
+        ] {
+            if state & flag != 0 {
+                write!(f, "{}{}", separator, name)?;
+                separator = "|";
+            }
+        }
+        Ok(())
     }
 }
 
@@ -0,0 +1,191 @@
+//! A lock-free multi-producer multi-consumer unbounded queue which can be created in
+//! a `const` context, such as a `static` item.
+//!
+//! The [`StaticQueue`] wraps a [`RawQueue`] without any pool of drained nodes, as a
+//! pool can't be allocated in a `const` context. Creating it doesn't allocate, as its
+//! first node is only installed by the first push.
+//!
+//! [`RawQueue`]: crate::raw::RawQueue
+
+#[cfg(feature = "std")]
+use crate::error::PopTimeoutError;
+use crate::error::{PushError, TryPopError};
+#[cfg(feature = "metrics")]
+use crate::metrics::QueueStats;
+use crate::node::DEFAULT_NODE_CAPACITY;
+use crate::raw::RawQueue;
+use crate::wait::{WaitStrategy, Yield};
+
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// A lock-free multi-producer multi-consumer unbounded queue which can be created in
+/// a `const` context, such as a `static` item.
+///
+/// It behaves like a [`Queue`], except that it's shared by reference instead of
+/// being cloned. Drained nodes are freed instead of being kept for reuse.
+///
+/// # Examples
+///
+/// ```
+/// use lf_queue::StaticQueue;
+/// use std::thread;
+///
+/// static EVENTS: StaticQueue<usize> = StaticQueue::new();
+///
+/// let th = thread::spawn(|| {
+///     for i in 0..3 {
+///         EVENTS.push(i);
+///     }
+/// });
+///
+/// th.join().unwrap();
+///
+/// assert_eq!(EVENTS.pop(), Some(0));
+/// assert_eq!(EVENTS.pop(), Some(1));
+/// assert_eq!(EVENTS.pop(), Some(2));
+/// assert_eq!(EVENTS.pop(), None);
+/// ```
+///
+/// [`Queue`]: crate::queue::Queue
+pub struct StaticQueue<T, const N: usize = DEFAULT_NODE_CAPACITY, W = Yield> {
+    inner: RawQueue<T, N, W>,
+}
+
+impl<T, const N: usize, W: WaitStrategy> StaticQueue<T, N, W> {
+    /// Creates a new [`StaticQueue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::StaticQueue;
+    ///
+    /// static QUEUE: StaticQueue<usize> = StaticQueue::new();
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            inner: RawQueue::new_unpooled(),
+        }
+    }
+
+    /// Push an item into the [`StaticQueue`], see [`Queue::push`].
+    ///
+    /// [`Queue::push`]: crate::queue::Queue::push
+    pub fn push(&self, item: T) {
+        self.inner.push(item);
+    }
+
+    /// Push an item into the [`StaticQueue`], unless it's closed, see
+    /// [`Queue::try_push`].
+    ///
+    /// [`Queue::try_push`]: crate::queue::Queue::try_push
+    pub fn try_push(&self, item: T) -> Result<(), PushError<T>> {
+        self.inner.try_push(item)
+    }
+
+    /// Push a batch of items into the [`StaticQueue`], see [`Queue::push_batch`].
+    ///
+    /// [`Queue::push_batch`]: crate::queue::Queue::push_batch
+    pub fn push_batch<I: IntoIterator<Item = T>>(&self, items: I) {
+        self.inner.push_batch(items);
+    }
+
+    /// Closes the [`StaticQueue`], see [`Queue::close`].
+    ///
+    /// [`Queue::close`]: crate::queue::Queue::close
+    pub fn close(&self) -> bool {
+        self.inner.close()
+    }
+
+    /// Returns `true` if the [`StaticQueue`] is closed.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Returns the number of items in the [`StaticQueue`], see [`Queue::len`].
+    ///
+    /// [`Queue::len`]: crate::queue::Queue::len
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the [`StaticQueue`] is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns a snapshot of the operations counted by the [`StaticQueue`], see
+    /// [`Queue::stats`].
+    ///
+    /// [`Queue::stats`]: crate::queue::Queue::stats
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> QueueStats {
+        self.inner.stats()
+    }
+
+    /// Pop an item from the [`StaticQueue`], see [`Queue::pop`].
+    ///
+    /// [`Queue::pop`]: crate::queue::Queue::pop
+    pub fn pop(&self) -> Option<T> {
+        self.inner.pop()
+    }
+
+    /// Pop up to `max` items from the [`StaticQueue`] into `buf`, see
+    /// [`Queue::pop_batch`].
+    ///
+    /// [`Queue::pop_batch`]: crate::queue::Queue::pop_batch
+    pub fn pop_batch(&self, buf: &mut Vec<T>, max: usize) -> usize {
+        self.inner.pop_batch(buf, max)
+    }
+
+    /// Try to pop an item from the [`StaticQueue`] without waiting for any other
+    /// thread, see [`Queue::try_pop`].
+    ///
+    /// [`Queue::try_pop`]: crate::queue::Queue::try_pop
+    pub fn try_pop(&self) -> Result<T, TryPopError> {
+        self.inner.try_pop()
+    }
+
+    /// Pop an item from the [`StaticQueue`], blocking the current thread until an
+    /// item is available, see [`Queue::pop_blocking`].
+    ///
+    /// [`Queue::pop_blocking`]: crate::queue::Queue::pop_blocking
+    #[cfg(feature = "std")]
+    pub fn pop_blocking(&self) -> Option<T> {
+        self.inner.pop_blocking()
+    }
+
+    /// Pop an item from the [`StaticQueue`], blocking the current thread for at most
+    /// `timeout`, see [`Queue::pop_timeout`].
+    ///
+    /// [`Queue::pop_timeout`]: crate::queue::Queue::pop_timeout
+    #[cfg(feature = "std")]
+    pub fn pop_timeout(&self, timeout: Duration) -> Result<T, PopTimeoutError> {
+        self.inner.pop_timeout(timeout)
+    }
+
+    /// Pop an item from the [`StaticQueue`], blocking the current thread until the
+    /// `deadline` is reached, see [`Queue::pop_deadline`].
+    ///
+    /// [`Queue::pop_deadline`]: crate::queue::Queue::pop_deadline
+    #[cfg(feature = "std")]
+    pub fn pop_deadline(&self, deadline: Instant) -> Result<T, PopTimeoutError> {
+        self.inner.pop_deadline(deadline)
+    }
+}
+
+impl<T, const N: usize, W> fmt::Debug for StaticQueue<T, N, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt_state("StaticQueue", f)
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy> Default for StaticQueue<T, N, W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,231 @@
+//! A [`Queue`] measuring how long its items wait before being popped.
+//!
+//! The [`TimedQueue`] stores each item along with the instant it was pushed at. When
+//! the item is popped, the time it spent in the queue is recorded into a
+//! [`LatencyHistogram`] shared by every handle of the [`TimedQueue`].
+//!
+//! [`Queue`]: crate::queue::Queue
+
+use crate::error::{PopTimeoutError, PushError, TryPopError};
+use crate::histogram::LatencyHistogram;
+use crate::node::DEFAULT_NODE_CAPACITY;
+use crate::raw::RawQueue;
+use crate::variant::sync::Arc;
+use crate::wait::{WaitStrategy, Yield};
+
+use alloc::vec::Vec;
+use core::time::Duration;
+use std::time::Instant;
+
+/// A lock-free multi-producer multi-consumer unbounded queue measuring how long
+/// its items wait before being popped.
+///
+/// It behaves like a [`Queue`], except that each item is stamped with the instant
+/// it's pushed at. Popping an item records the time it spent in the queue into the
+/// [`LatencyHistogram`] returned by [`TimedQueue::latency`].
+///
+/// This type requires the `latency` feature.
+///
+/// # Examples
+///
+/// ```
+/// use lf_queue::TimedQueue;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let queue = TimedQueue::<usize>::new();
+///
+/// let q = queue.clone();
+/// let th = thread::spawn(move || {
+///     for i in 0..100 {
+///         q.push(i);
+///     }
+/// });
+///
+/// th.join().unwrap();
+/// thread::sleep(Duration::from_millis(1));
+/// while queue.pop().is_some() {}
+///
+/// let latency = queue.latency();
+/// assert_eq!(latency.count(), 100);
+/// assert!(latency.percentile(50.0).unwrap() >= Duration::from_millis(1));
+/// ```
+///
+/// [`Queue`]: crate::queue::Queue
+#[derive(Debug)]
+pub struct TimedQueue<T, const N: usize = DEFAULT_NODE_CAPACITY, W = Yield> {
+    inner: Arc<Inner<T, N, W>>,
+}
+
+#[derive(Debug)]
+struct Inner<T, const N: usize, W> {
+    /// Holds the items along with the instant they were pushed at.
+    queue: RawQueue<(Instant, T), N, W>,
+
+    /// Records how long the popped items waited in the queue.
+    latency: LatencyHistogram,
+}
+
+impl<T, const N: usize, W: WaitStrategy> TimedQueue<T, N, W> {
+    /// Creates a new [`TimedQueue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lf_queue::TimedQueue;
+    ///
+    /// let queue = TimedQueue::<usize>::new();
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                queue: RawQueue::new(),
+                latency: LatencyHistogram::new(),
+            }),
+        }
+    }
+
+    /// Returns the histogram of the time the popped items spent in the
+    /// [`TimedQueue`], shared by every handle of the [`TimedQueue`].
+    pub fn latency(&self) -> &LatencyHistogram {
+        &self.inner.latency
+    }
+
+    /// Push an item into the [`TimedQueue`], see [`Queue::push`].
+    ///
+    /// [`Queue::push`]: crate::queue::Queue::push
+    pub fn push(&self, item: T) {
+        self.inner.queue.push((Instant::now(), item));
+    }
+
+    /// Push an item into the [`TimedQueue`], unless it's closed, see
+    /// [`Queue::try_push`].
+    ///
+    /// [`Queue::try_push`]: crate::queue::Queue::try_push
+    pub fn try_push(&self, item: T) -> Result<(), PushError<T>> {
+        self.inner
+            .queue
+            .try_push((Instant::now(), item))
+            .map_err(|e| PushError::Closed(e.into_inner().1))
+    }
+
+    /// Push a batch of items into the [`TimedQueue`], see [`Queue::push_batch`].
+    ///
+    /// The items are stamped one by one, as they're pushed.
+    ///
+    /// [`Queue::push_batch`]: crate::queue::Queue::push_batch
+    pub fn push_batch<I: IntoIterator<Item = T>>(&self, items: I) {
+        self.inner
+            .queue
+            .push_batch(items.into_iter().map(|item| (Instant::now(), item)));
+    }
+
+    /// Closes the [`TimedQueue`], see [`Queue::close`].
+    ///
+    /// [`Queue::close`]: crate::queue::Queue::close
+    pub fn close(&self) -> bool {
+        self.inner.queue.close()
+    }
+
+    /// Returns `true` if the [`TimedQueue`] is closed.
+    pub fn is_closed(&self) -> bool {
+        self.inner.queue.is_closed()
+    }
+
+    /// Returns the number of items in the [`TimedQueue`], see [`Queue::len`].
+    ///
+    /// [`Queue::len`]: crate::queue::Queue::len
+    pub fn len(&self) -> usize {
+        self.inner.queue.len()
+    }
+
+    /// Returns `true` if the [`TimedQueue`] is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.queue.is_empty()
+    }
+
+    /// Pop an item from the [`TimedQueue`], see [`Queue::pop`].
+    ///
+    /// [`Queue::pop`]: crate::queue::Queue::pop
+    pub fn pop(&self) -> Option<T> {
+        self.inner.queue.pop().map(|item| self.inner.record(item))
+    }
+
+    /// Try to pop an item from the [`TimedQueue`] without waiting for any other
+    /// thread, see [`Queue::try_pop`].
+    ///
+    /// [`Queue::try_pop`]: crate::queue::Queue::try_pop
+    pub fn try_pop(&self) -> Result<T, TryPopError> {
+        self.inner
+            .queue
+            .try_pop()
+            .map(|item| self.inner.record(item))
+    }
+
+    /// Pop an item from the [`TimedQueue`], blocking the current thread until an
+    /// item is available, see [`Queue::pop_blocking`].
+    ///
+    /// [`Queue::pop_blocking`]: crate::queue::Queue::pop_blocking
+    pub fn pop_blocking(&self) -> Option<T> {
+        self.inner
+            .queue
+            .pop_blocking()
+            .map(|item| self.inner.record(item))
+    }
+
+    /// Pop an item from the [`TimedQueue`], blocking the current thread for at most
+    /// `timeout`, see [`Queue::pop_timeout`].
+    ///
+    /// [`Queue::pop_timeout`]: crate::queue::Queue::pop_timeout
+    pub fn pop_timeout(&self, timeout: Duration) -> Result<T, PopTimeoutError> {
+        self.inner
+            .queue
+            .pop_timeout(timeout)
+            .map(|item| self.inner.record(item))
+    }
+
+    /// Pop an item from the [`TimedQueue`], blocking the current thread until
+    /// `deadline`, see [`Queue::pop_deadline`].
+    ///
+    /// [`Queue::pop_deadline`]: crate::queue::Queue::pop_deadline
+    pub fn pop_deadline(&self, deadline: Instant) -> Result<T, PopTimeoutError> {
+        self.inner
+            .queue
+            .pop_deadline(deadline)
+            .map(|item| self.inner.record(item))
+    }
+
+    /// Pop up to `max` items from the [`TimedQueue`] into `buf`, see
+    /// [`Queue::pop_batch`].
+    ///
+    /// The time spent in the [`TimedQueue`] is recorded for each popped item.
+    ///
+    /// [`Queue::pop_batch`]: crate::queue::Queue::pop_batch
+    pub fn pop_batch(&self, buf: &mut Vec<T>, max: usize) -> usize {
+        self.inner
+            .queue
+            .pop_batch_map(buf, max, |item| self.inner.record(item))
+    }
+}
+
+impl<T, const N: usize, W> Inner<T, N, W> {
+    /// Records the time a popped item spent in the queue, and returns the item.
+    fn record(&self, (pushed_at, item): (Instant, T)) -> T {
+        self.latency.record(pushed_at.elapsed());
+        item
+    }
+}
+
+impl<T, const N: usize, W> Clone for TimedQueue<T, N, W> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, const N: usize, W: WaitStrategy> Default for TimedQueue<T, N, W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,4 +1,7 @@
-//! Switch from [`std`] to [`loom`] for [`std::cell`], [`std::sync`] and [`std::thread`] when using the `--cfg loom` flag.
+//! Switch from [`std`] to [`loom`] for [`std::cell`], [`std::hint`], [`std::sync`] and [`std::thread`] when using the `--cfg loom` flag.
+//!
+//! Without the `std` feature, [`core`] and [`alloc`] provide the cells, the hints and the
+//! atomics, while waiting for another thread spins instead of yielding to the scheduler.
 //!
 //! [`loom`]: https://docs.rs/loom/
 
@@ -6,11 +9,11 @@
 pub(crate) mod cell {
     #[derive(Debug)]
     #[repr(transparent)]
-    pub(crate) struct UnsafeCell<T>(std::cell::UnsafeCell<T>);
+    pub(crate) struct UnsafeCell<T>(core::cell::UnsafeCell<T>);
 
     impl<T> UnsafeCell<T> {
         pub(crate) const fn new(data: T) -> UnsafeCell<T> {
-            UnsafeCell(std::cell::UnsafeCell::new(data))
+            UnsafeCell(core::cell::UnsafeCell::new(data))
         }
 
         pub(crate) fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
@@ -25,18 +28,20 @@ pub(crate) mod cell {
 
 #[cfg(not(loom))]
 pub(crate) mod sync {
-    pub(crate) use std::sync::Arc;
+    pub(crate) use alloc::sync::Arc;
+    #[cfg(feature = "std")]
+    pub(crate) use std::sync::{Condvar, Mutex};
 
     pub(crate) mod atomic {
-        pub(crate) use std::sync::atomic::{fence, AtomicPtr, Ordering};
+        pub(crate) use core::sync::atomic::{fence, AtomicPtr, Ordering};
 
         #[derive(Debug)]
         #[repr(transparent)]
-        pub(crate) struct AtomicUsize(std::sync::atomic::AtomicUsize);
+        pub(crate) struct AtomicUsize(core::sync::atomic::AtomicUsize);
 
         impl AtomicUsize {
             pub(crate) const fn new(v: usize) -> Self {
-                Self(std::sync::atomic::AtomicUsize::new(v))
+                Self(core::sync::atomic::AtomicUsize::new(v))
             }
 
             pub(crate) fn load(&self, order: Ordering) -> usize {
@@ -61,6 +66,10 @@ pub(crate) mod sync {
                 self.0.fetch_add(val, order)
             }
 
+            pub(crate) fn fetch_sub(&self, val: usize, order: Ordering) -> usize {
+                self.0.fetch_sub(val, order)
+            }
+
             pub(crate) fn fetch_or(&self, val: usize, order: Ordering) -> usize {
                 self.0.fetch_or(val, order)
             }
@@ -69,11 +78,38 @@ pub(crate) mod sync {
 }
 
 #[cfg(not(loom))]
+pub(crate) use core::hint;
+#[cfg(all(not(loom), feature = "std"))]
 pub(crate) use std::thread;
 
+#[cfg(all(not(loom), not(feature = "std")))]
+pub(crate) mod thread {
+    use core::time::Duration;
+
+    /// There is no scheduler to yield to, so the thread spins instead.
+    pub(crate) fn yield_now() {
+        core::hint::spin_loop();
+    }
+
+    /// Threads can't be parked, so the thread spins instead.
+    pub(crate) fn park_timeout(_dur: Duration) {
+        core::hint::spin_loop();
+    }
+}
+
 #[cfg(loom)]
 pub(crate) use loom::cell;
 #[cfg(loom)]
+pub(crate) use loom::hint;
+#[cfg(loom)]
 pub(crate) use loom::sync;
+
 #[cfg(loom)]
-pub(crate) use loom::thread;
+pub(crate) mod thread {
+    pub(crate) use loom::thread::*;
+
+    /// Loom doesn't model time, so parking the thread for a while yields instead.
+    pub(crate) fn park_timeout(_dur: std::time::Duration) {
+        yield_now();
+    }
+}
@@ -0,0 +1,135 @@
+//! Defines how a thread waits for another one to make progress.
+//!
+//! A few operations of the [`Queue`] can't complete until another thread finishes
+//! its own: a consumer waits for the producer of the item it claimed to write it,
+//! and both wait for the next node to be installed once they reach the end of the
+//! current one. These waits are short, so the thread keeps checking instead of being
+//! parked until notified, calling [`WaitStrategy::wait`] between two checks.
+//!
+//! Without the `std` feature, there is no scheduler to yield to, so the strategies
+//! yielding or parking the thread spin instead.
+//!
+//! [`Queue`]: crate::queue::Queue
+
+use crate::variant::hint;
+use crate::variant::thread;
+
+use core::time::Duration;
+
+/// Defines how a thread waits for another thread to make progress.
+///
+/// The [`Queue`] calls [`WaitStrategy::wait`] between two checks of the condition
+/// it's waiting for, with the number of times it already waited for it. It's up to
+/// the strategy to either spin, yield to the scheduler, or put the thread to sleep.
+///
+/// # Examples
+///
+/// ```
+/// use lf_queue::{Queue, WaitStrategy, DEFAULT_NODE_CAPACITY};
+///
+/// /// Spins for a while, then gives up the rest of the time slice.
+/// #[derive(Debug)]
+/// struct SpinThenYield;
+///
+/// impl WaitStrategy for SpinThenYield {
+///     fn wait(step: u32) {
+///         if step < 100 {
+///             std::hint::spin_loop();
+///         } else {
+///             std::thread::yield_now();
+///         }
+///     }
+/// }
+///
+/// let queue = Queue::<usize, DEFAULT_NODE_CAPACITY, SpinThenYield>::new();
+/// queue.push(1);
+///
+/// assert_eq!(queue.pop(), Some(1));
+/// ```
+///
+/// [`Queue`]: crate::queue::Queue
+pub trait WaitStrategy {
+    /// Waits before checking the condition once again.
+    ///
+    /// `step` reports how many times the caller already waited for the same
+    /// condition, starting from zero.
+    fn wait(step: u32);
+}
+
+/// Busy-waits, hinting the processor that the thread is spinning.
+///
+/// The thread never gives up its time slice, which suits threads pinned to their
+/// own core. Otherwise, it may burn the time the thread it's waiting for needs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Spin;
+
+impl WaitStrategy for Spin {
+    #[inline]
+    fn wait(_step: u32) {
+        hint::spin_loop();
+    }
+}
+
+/// Spins for an exponentially growing number of iterations, then yields to the
+/// scheduler once the wait lasts.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Backoff;
+
+impl Backoff {
+    /// Reports the number of steps after which the thread yields instead of spinning.
+    const SPIN_LIMIT: u32 = 6;
+}
+
+impl WaitStrategy for Backoff {
+    #[inline]
+    fn wait(step: u32) {
+        if step < Self::SPIN_LIMIT {
+            for _ in 0..1 << step {
+                hint::spin_loop();
+            }
+        } else {
+            thread::yield_now();
+        }
+    }
+}
+
+/// Gives up the rest of the time slice to the scheduler.
+///
+/// This is the strategy used by a [`Queue`] which doesn't specify one.
+///
+/// [`Queue`]: crate::queue::Queue
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Yield;
+
+impl WaitStrategy for Yield {
+    #[inline]
+    fn wait(_step: u32) {
+        thread::yield_now();
+    }
+}
+
+/// Spins for `SPINS` steps, then parks the thread for an exponentially growing
+/// duration, up to a millisecond.
+///
+/// Parking frees the core for other threads when the thread it's waiting for has
+/// been preempted, at the cost of a higher latency once it makes progress.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParkAfter<const SPINS: u32>;
+
+impl<const SPINS: u32> ParkAfter<SPINS> {
+    /// Reports the longest duration the thread is parked for, as a power of two
+    /// of microseconds.
+    const MAX_PARK_SHIFT: u32 = 10;
+}
+
+impl<const SPINS: u32> WaitStrategy for ParkAfter<SPINS> {
+    #[inline]
+    fn wait(step: u32) {
+        if step < SPINS {
+            hint::spin_loop();
+        } else {
+            let shift = (step - SPINS).min(Self::MAX_PARK_SHIFT);
+            thread::park_timeout(Duration::from_micros(1 << shift));
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Parks the threads waiting for the [`Queue`] to make progress.
+//!
+//! A waiting thread registers itself by incrementing the number of sleepers, while
+//! holding the lock, before checking the [`Queue`] one last time and parking on the
+//! condition variable. Notifying threads only load the number of sleepers and take
+//! the lock when there is at least one of them, keeping the uncontended path free of
+//! any lock.
+//!
+//! Both sides issue a `SeqCst` fence between their update and their check, so either
+//! the waiting thread sees the update, or the notifying thread sees the sleeper. As
+//! the sleeper holds the lock until it's parked, it can't miss the notification.
+//!
+//! With the `async` feature, tasks register their [`Waker`] the same way and are counted
+//! as sleepers until they're either notified or unregistered. A notified task which
+//! gives up waiting must pass the notification on to another sleeper.
+//!
+//! Without the `std` feature, threads can't be parked, so there is never any sleeper
+//! to notify.
+//!
+//! [`Waker`]: std::task::Waker
+//! [`Queue`]: crate::queue::Queue
+
+#[cfg(feature = "std")]
+use crate::variant::sync::atomic::{fence, AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use crate::variant::sync::{Condvar, Mutex};
+
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::task::Waker;
+#[cfg(feature = "async")]
+use std::collections::VecDeque;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// Parks the threads waiting for the [`Queue`] to make progress.
+///
+/// [`Queue`]: crate::queue::Queue
+#[derive(Debug)]
+pub(crate) struct Waiters {
+    /// Reports the number of threads registered as sleepers.
+    #[cfg(feature = "std")]
+    sleepers: AtomicUsize,
+
+    /// Held by a sleeper from the time it registers until it's parked.
+    #[cfg(feature = "std")]
+    lock: Mutex<Registry>,
+
+    /// Parks the sleepers.
+    #[cfg(feature = "std")]
+    condvar: Condvar,
+}
+
+#[cfg(not(feature = "std"))]
+impl Waiters {
+    pub(crate) const fn new() -> Self {
+        Self {}
+    }
+
+    /// Threads can't be parked, so there is no sleeper to wake up.
+    pub(crate) fn notify_one(&self) {}
+
+    /// Threads can't be parked, so there is no sleeper to wake up.
+    pub(crate) fn notify_all(&self) {}
+}
+
+#[cfg(feature = "std")]
+impl Waiters {
+    #[cfg(not(loom))]
+    pub(crate) const fn new() -> Self {
+        Self {
+            sleepers: AtomicUsize::new(0),
+            lock: Mutex::new(Registry::new()),
+            condvar: Condvar::new(),
+        }
+    }
+
+    // In loom, `Mutex::new` and `Condvar::new` are not `const fn`.
+    #[cfg(loom)]
+    pub(crate) fn new() -> Self {
+        Self {
+            sleepers: AtomicUsize::new(0),
+            lock: Mutex::new(Registry::new()),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Wakes up one of the sleepers, if any.
+    pub(crate) fn notify_one(&self) {
+        if self.has_sleepers() {
+            let mut registry = self.lock.lock().unwrap();
+
+            if let Some(waker) = registry.pop_front() {
+                let _ = self.sleepers.fetch_sub(1, Ordering::SeqCst);
+                drop(registry);
+                waker.wake();
+                return;
+            }
+
+            self.condvar.notify_one();
+        }
+    }
+
+    /// Wakes up all the sleepers, if any.
+    pub(crate) fn notify_all(&self) {
+        if self.has_sleepers() {
+            let mut registry = self.lock.lock().unwrap();
+
+            let mut wakers = Vec::new();
+            while let Some(waker) = registry.pop_front() {
+                let _ = self.sleepers.fetch_sub(1, Ordering::SeqCst);
+                wakers.push(waker);
+            }
+
+            self.condvar.notify_all();
+            drop(registry);
+
+            for waker in wakers {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Registers the `waker` of a task as a sleeper, or refreshes it if the task
+    /// is still registered under `id`.
+    ///
+    /// Returns the id the task is registered under, and whether it was already
+    /// registered. A task that isn't registered anymore has been notified, and
+    /// must check the [`Queue`] once again before waiting.
+    ///
+    /// [`Queue`]: crate::queue::Queue
+    #[cfg(feature = "async")]
+    pub(crate) fn register(&self, id: Option<usize>, waker: &Waker) -> (usize, bool) {
+        let mut registry = self.lock.lock().unwrap();
+
+        if let Some(id) = id {
+            if let Some((_, w)) = registry.wakers.iter_mut().find(|(i, _)| *i == id) {
+                if !w.will_wake(waker) {
+                    *w = waker.clone();
+                }
+
+                return (id, true);
+            }
+        }
+
+        let id = registry.next_id;
+        registry.next_id = registry.next_id.wrapping_add(1);
+        registry.wakers.push_back((id, waker.clone()));
+
+        // Registers the task as a sleeper before it checks the queue once again.
+        let _ = self.sleepers.fetch_add(1, Ordering::SeqCst);
+        fence(Ordering::SeqCst);
+
+        (id, false)
+    }
+
+    /// Unregisters the task registered under `id`.
+    ///
+    /// Returns `false` if the task had already been notified.
+    #[cfg(feature = "async")]
+    pub(crate) fn unregister(&self, id: usize) -> bool {
+        let mut registry = self.lock.lock().unwrap();
+
+        match registry.wakers.iter().position(|(i, _)| *i == id) {
+            Some(position) => {
+                let _ = registry.wakers.remove(position);
+                let _ = self.sleepers.fetch_sub(1, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Calls `f` until it returns a value, parking the thread between two calls.
+    ///
+    /// Returns `None` if the `deadline` is reached before `f` returns a value.
+    pub(crate) fn wait_until<R>(
+        &self,
+        deadline: Option<Instant>,
+        mut f: impl FnMut() -> Option<R>,
+    ) -> Option<R> {
+        if let Some(r) = f() {
+            return Some(r);
+        }
+
+        let mut guard = self.lock.lock().unwrap();
+
+        loop {
+            // Registers this thread as a sleeper before checking one last time.
+            let _ = self.sleepers.fetch_add(1, Ordering::SeqCst);
+            fence(Ordering::SeqCst);
+
+            let r = f();
+            if r.is_some() {
+                let _ = self.sleepers.fetch_sub(1, Ordering::SeqCst);
+                return r;
+            }
+
+            guard = match deadline {
+                None => self.condvar.wait(guard).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        let _ = self.sleepers.fetch_sub(1, Ordering::SeqCst);
+                        return None;
+                    }
+
+                    self.condvar.wait_timeout(guard, deadline - now).unwrap().0
+                }
+            };
+
+            let _ = self.sleepers.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    fn has_sleepers(&self) -> bool {
+        // Pairs with the fence issued by the sleepers in `wait_until`.
+        fence(Ordering::SeqCst);
+        self.sleepers.load(Ordering::SeqCst) != 0
+    }
+}
+
+/// Holds the tasks registered as sleepers.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct Registry {
+    /// The registered tasks, in the order they registered.
+    #[cfg(feature = "async")]
+    wakers: VecDeque<(usize, Waker)>,
+
+    /// The id of the next registered task.
+    #[cfg(feature = "async")]
+    next_id: usize,
+}
+
+#[cfg(feature = "std")]
+impl Registry {
+    const fn new() -> Self {
+        Self {
+            #[cfg(feature = "async")]
+            wakers: VecDeque::new(),
+            #[cfg(feature = "async")]
+            next_id: 0,
+        }
+    }
+
+    /// Removes the task which registered first, if any.
+    fn pop_front(&mut self) -> Option<Waker> {
+        #[cfg(feature = "async")]
+        return self.wakers.pop_front().map(|(_, waker)| waker);
+
+        #[cfg(not(feature = "async"))]
+        return None;
+    }
+}
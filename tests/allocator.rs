@@ -0,0 +1,161 @@
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+
+use lf_queue::{NodeAllocator, Queue, RawQueue, Yield};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+// Counts the nodes allocated and freed by the queues sharing it.
+#[derive(Debug, Clone, Default)]
+struct CountingAlloc(Arc<Counters>);
+
+#[derive(Debug, Default)]
+struct Counters {
+    allocs: AtomicUsize,
+    frees: AtomicUsize,
+}
+
+impl CountingAlloc {
+    fn allocs(&self) -> usize {
+        self.0.allocs.load(Ordering::SeqCst)
+    }
+
+    fn frees(&self) -> usize {
+        self.0.frees.load(Ordering::SeqCst)
+    }
+}
+
+unsafe impl NodeAllocator for CountingAlloc {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.0.allocs.fetch_add(1, Ordering::SeqCst);
+        NonNull::new(unsafe { System.alloc(layout) })
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.frees.fetch_add(1, Ordering::SeqCst);
+        unsafe { System.dealloc(ptr.as_ptr(), layout) }
+    }
+}
+
+// cargo test --package lf-queue --test allocator -- test_mpmc --exact --nocapture
+#[test]
+fn test_mpmc() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    let alloc = CountingAlloc::default();
+    let queue: Queue<usize, 7, Yield, CountingAlloc> = Queue::new_in(alloc.clone());
+
+    let ths: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    q.push(i);
+                }
+            })
+        })
+        .chain((0..CONCURRENCY).map(|_| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                for _ in 0..COUNT / 2 {
+                    // Mixes both ways of popping, as peeking consumers retire the
+                    // drained nodes instead of recycling them.
+                    while q.pop().is_none() {}
+                    while q.try_pop().is_err() {}
+                }
+            })
+        }))
+        .collect();
+
+    for th in ths {
+        th.join().unwrap();
+    }
+
+    assert!(queue.is_empty());
+    assert!(alloc.allocs() > 0);
+
+    drop(queue);
+    assert_eq!(alloc.allocs(), alloc.frees());
+}
+
+// cargo test --package lf-queue --test allocator -- test_pool --exact --nocapture
+#[test]
+fn test_pool() {
+    const NODES: usize = 4;
+    let alloc = CountingAlloc::default();
+    let queue: Queue<usize, 7, Yield, CountingAlloc> =
+        Queue::with_pool_capacity_in(NODES, alloc.clone());
+
+    queue.push_batch(0..7 * NODES);
+    while queue.pop().is_some() {}
+    let allocs = alloc.allocs();
+
+    // Drained nodes are kept in the pool, and reused without any allocation.
+    queue.push_batch(0..7 * NODES);
+    while queue.pop().is_some() {}
+    assert_eq!(alloc.allocs(), allocs);
+
+    queue.shrink_to_fit();
+    assert_eq!(alloc.frees(), NODES);
+
+    drop(queue);
+    assert_eq!(alloc.allocs(), alloc.frees());
+}
+
+// cargo test --package lf-queue --test allocator -- test_drop_remaining_items --exact --nocapture
+#[test]
+fn test_drop_remaining_items() {
+    let alloc = CountingAlloc::default();
+
+    // Dropping a queue without any node.
+    drop(RawQueue::<usize, 7, Yield, CountingAlloc>::new_in(
+        alloc.clone(),
+    ));
+    assert_eq!(alloc.allocs(), 0);
+
+    let queue: RawQueue<usize, 7, Yield, CountingAlloc> = RawQueue::new_in(alloc.clone());
+    queue.push_batch(0..20);
+    for i in 0..5 {
+        assert_eq!(queue.pop(), Some(i));
+    }
+
+    drop(queue);
+    assert_eq!(alloc.allocs(), 3);
+    assert_eq!(alloc.frees(), 3);
+}
+
+// cargo +nightly test --features nightly --package lf-queue --test allocator -- test_allocator_api --exact --nocapture
+#[cfg(feature = "nightly")]
+#[test]
+fn test_allocator_api() {
+    use std::alloc::{AllocError, Allocator};
+
+    #[derive(Debug, Clone, Default)]
+    struct CountingAllocator(CountingAlloc);
+
+    unsafe impl Allocator for CountingAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let counters = &(self.0).0;
+            counters.allocs.fetch_add(1, Ordering::SeqCst);
+            Allocator::allocate(&System, layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            let counters = &(self.0).0;
+            counters.frees.fetch_add(1, Ordering::SeqCst);
+            unsafe { Allocator::deallocate(&System, ptr, layout) }
+        }
+    }
+
+    let alloc = CountingAllocator::default();
+    let queue: Queue<usize, 7, Yield, CountingAllocator> = Queue::new_in(alloc.clone());
+
+    queue.push_batch(0..20);
+    assert_eq!(queue.len(), 20);
+    drop(queue);
+
+    assert_eq!(alloc.0.allocs(), 3);
+    assert_eq!(alloc.0.frees(), 3);
+}
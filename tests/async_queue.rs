@@ -0,0 +1,209 @@
+#![cfg(feature = "async")]
+
+use futures_core::Stream;
+use lf_queue::Queue;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+// Run all tests:
+//
+// cargo test --package lf-queue --features async --test async_queue
+
+/// Wakes up the thread blocked on a future.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Minimal executor running a future to completion on the current thread.
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = Box::pin(fut);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Counts how many times a task has been woken up.
+#[derive(Default)]
+struct CountingWaker(AtomicUsize);
+
+impl CountingWaker {
+    fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Polls the next item of a stream.
+fn next<S: Stream + Unpin>(stream: &mut S) -> impl Future<Output = Option<S::Item>> + '_ {
+    std::future::poll_fn(move |cx| Pin::new(&mut *stream).poll_next(cx))
+}
+
+// cargo test --package lf-queue --features async --test async_queue -- test_pop_async --exact --nocapture
+#[test]
+fn test_pop_async() {
+    const COUNT: usize = 7 * 3;
+    let queue: Queue<usize, 7> = Queue::new();
+
+    for i in 0..COUNT {
+        queue.push(i);
+    }
+
+    for i in 0..COUNT {
+        assert_eq!(block_on(queue.pop_async()), Some(i));
+    }
+}
+
+// cargo test --package lf-queue --features async --test async_queue -- test_pop_async_mpmc --exact --nocapture
+#[test]
+fn test_pop_async_mpmc() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    let queue: Queue<usize> = Queue::new();
+    let items = Arc::new((0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>());
+
+    let consumers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            let its = items.clone();
+            thread::spawn(move || {
+                block_on(async {
+                    for _ in 0..COUNT {
+                        its[q.pop_async().await.unwrap()].fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+            })
+        })
+        .collect();
+
+    let producers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    q.push(i);
+                }
+            })
+        })
+        .collect();
+
+    for th in producers.into_iter().chain(consumers) {
+        th.join().unwrap();
+    }
+
+    for c in &*items {
+        assert_eq!(c.load(Ordering::SeqCst), CONCURRENCY);
+    }
+
+    assert!(queue.pop().is_none());
+}
+
+// cargo test --package lf-queue --features async --test async_queue -- test_pop_async_wake --exact --nocapture
+#[test]
+fn test_pop_async_wake() {
+    let queue: Queue<usize> = Queue::new();
+    let waker = Arc::new(CountingWaker::default());
+    let w = Waker::from(waker.clone());
+    let mut cx = Context::from_waker(&w);
+
+    let mut fut = queue.pop_async();
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+    assert_eq!(waker.count(), 0);
+
+    queue.push(1);
+    assert_eq!(waker.count(), 1);
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(Some(1)));
+}
+
+// cargo test --package lf-queue --features async --test async_queue -- test_pop_async_close --exact --nocapture
+#[test]
+fn test_pop_async_close() {
+    let queue: Queue<usize> = Queue::new();
+    let waker = Arc::new(CountingWaker::default());
+    let w = Waker::from(waker.clone());
+    let mut cx = Context::from_waker(&w);
+
+    let mut fut = queue.pop_async();
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+
+    queue.close();
+    assert_eq!(waker.count(), 1);
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(None));
+}
+
+// cargo test --package lf-queue --features async --test async_queue -- test_pop_async_cancel --exact --nocapture
+#[test]
+fn test_pop_async_cancel() {
+    let queue: Queue<usize> = Queue::new();
+    let waker1 = Arc::new(CountingWaker::default());
+    let waker2 = Arc::new(CountingWaker::default());
+    let w1 = Waker::from(waker1.clone());
+    let w2 = Waker::from(waker2.clone());
+
+    let mut fut1 = queue.pop_async();
+    let mut fut2 = queue.pop_async();
+    assert_eq!(
+        Pin::new(&mut fut1).poll(&mut Context::from_waker(&w1)),
+        Poll::Pending
+    );
+    assert_eq!(
+        Pin::new(&mut fut2).poll(&mut Context::from_waker(&w2)),
+        Poll::Pending
+    );
+
+    // The first task is notified but gives up, so the notification
+    // must be passed on to the second one.
+    queue.push(1);
+    assert_eq!(waker1.count(), 1);
+    assert_eq!(waker2.count(), 0);
+
+    drop(fut1);
+    assert_eq!(waker2.count(), 1);
+    assert_eq!(
+        Pin::new(&mut fut2).poll(&mut Context::from_waker(&w2)),
+        Poll::Ready(Some(1))
+    );
+}
+
+// cargo test --package lf-queue --features async --test async_queue -- test_stream --exact --nocapture
+#[test]
+fn test_stream() {
+    const COUNT: usize = 1_000;
+    let queue: Queue<usize> = Queue::new();
+    let mut stream = queue.stream();
+
+    let q = queue.clone();
+    let th = thread::spawn(move || {
+        for i in 0..COUNT {
+            q.push(i);
+        }
+        q.close();
+    });
+
+    block_on(async {
+        for i in 0..COUNT {
+            assert_eq!(next(&mut stream).await, Some(i));
+        }
+        assert_eq!(next(&mut stream).await, None);
+    });
+
+    th.join().unwrap();
+}
@@ -0,0 +1,277 @@
+#![cfg(feature = "std")]
+
+use lf_queue::{Backoff, BoundedQueue, ParkAfter, PushError, Spin, WaitStrategy, Yield};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+// cargo test --package lf-queue --test bounded -- test_spsc --exact --nocapture
+#[test]
+fn test_spsc() {
+    const COUNT: usize = 7 * 3;
+    let queue: BoundedQueue<usize> = BoundedQueue::new(COUNT);
+
+    for i in 0..COUNT {
+        queue.try_push(i).unwrap();
+    }
+
+    assert_eq!(queue.try_push(COUNT), Err(PushError::Full(COUNT)));
+
+    for i in 0..COUNT {
+        assert_eq!(i, queue.pop().unwrap());
+    }
+
+    assert!(queue.pop().is_none());
+}
+
+// cargo test --package lf-queue --test bounded -- test_mpmc --exact --nocapture
+#[test]
+fn test_mpmc() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    let queue: BoundedQueue<usize> = BoundedQueue::new(3);
+    let items = Arc::new((0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>());
+
+    let consumers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            let its = items.clone();
+            thread::spawn(move || {
+                for _ in 0..COUNT {
+                    let n = loop {
+                        if let Some(x) = q.pop() {
+                            break x;
+                        } else {
+                            thread::yield_now();
+                        }
+                    };
+                    its[n].fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        })
+        .collect();
+
+    let producers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    q.push(i);
+                }
+            })
+        })
+        .collect();
+
+    for th in producers.into_iter().chain(consumers) {
+        th.join().unwrap();
+    }
+
+    for c in &*items {
+        assert_eq!(c.load(Ordering::SeqCst), CONCURRENCY);
+    }
+
+    assert!(queue.pop().is_none());
+}
+
+// cargo test --package lf-queue --test bounded -- test_force_push --exact --nocapture
+#[test]
+fn test_force_push() {
+    const CAPACITY: usize = 5;
+    let queue: BoundedQueue<usize> = BoundedQueue::new(CAPACITY);
+
+    for i in 0..CAPACITY {
+        assert_eq!(queue.force_push(i), Ok(None));
+    }
+
+    // Overwrites the oldest items across several laps around the ring.
+    for i in CAPACITY..CAPACITY * 3 {
+        assert_eq!(queue.force_push(i), Ok(Some(i - CAPACITY)));
+    }
+
+    for i in CAPACITY * 2..CAPACITY * 3 {
+        assert_eq!(queue.pop(), Some(i));
+    }
+
+    assert!(queue.pop().is_none());
+}
+
+// cargo test --package lf-queue --test bounded -- test_zero_capacity --exact --nocapture
+#[test]
+#[should_panic(expected = "capacity must be non-zero")]
+fn test_zero_capacity() {
+    let _ = BoundedQueue::<usize>::new(0);
+}
+
+struct DropCounter<'a>(&'a AtomicUsize);
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// cargo test --package lf-queue --test bounded -- test_drop_remaining_items --exact --nocapture
+#[test]
+fn test_drop_remaining_items() {
+    const CAPACITY: usize = 5;
+    let drops = AtomicUsize::new(0);
+    let queue: BoundedQueue<DropCounter<'_>> = BoundedQueue::new(CAPACITY);
+
+    // Wraps around the ring, so the remaining items are split at its end.
+    for _ in 0..3 {
+        queue.push(DropCounter(&drops));
+    }
+    for _ in 0..3 {
+        drop(queue.pop());
+    }
+    for _ in 0..CAPACITY {
+        queue.push(DropCounter(&drops));
+    }
+    drop(queue.force_push(DropCounter(&drops)));
+
+    assert_eq!(drops.load(Ordering::SeqCst), 4);
+
+    drop(queue);
+    assert_eq!(drops.load(Ordering::SeqCst), 4 + CAPACITY);
+}
+
+// cargo test --package lf-queue --test bounded -- test_close --exact --nocapture
+#[test]
+fn test_close() {
+    let queue: BoundedQueue<usize> = BoundedQueue::new(2);
+    queue.push(1);
+
+    assert!(queue.close());
+    assert!(!queue.close());
+    assert!(queue.is_closed());
+
+    assert_eq!(queue.try_push(2), Err(PushError::Closed(2)));
+    assert_eq!(queue.force_push(3), Err(PushError::Closed(3)));
+
+    // The remaining items can still be popped.
+    assert_eq!(queue.pop(), Some(1));
+    assert_eq!(queue.pop(), None);
+}
+
+// cargo test --package lf-queue --test bounded -- test_close_wakes_up_producers --exact --nocapture
+#[test]
+fn test_close_wakes_up_producers() {
+    const CONCURRENCY: usize = 4;
+    let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+    queue.push(0);
+
+    let producers: Vec<_> = (0..CONCURRENCY)
+        .map(|i| {
+            let q = queue.clone();
+            thread::spawn(move || q.push(i))
+        })
+        .collect();
+
+    thread::sleep(std::time::Duration::from_millis(10));
+    queue.close();
+
+    for th in producers {
+        th.join().unwrap();
+    }
+
+    assert_eq!(queue.pop(), Some(0));
+    assert_eq!(queue.pop(), None);
+}
+
+// cargo test --package lf-queue --test bounded -- test_wait_strategy --exact --nocapture
+#[test]
+fn test_wait_strategy() {
+    fn check<W: WaitStrategy + 'static>() {
+        const COUNT: usize = 1_000;
+        const CONCURRENCY: usize = 2;
+
+        // A small ring makes the threads wait for each other more often.
+        let queue: BoundedQueue<usize, W> = BoundedQueue::new(2);
+
+        let producers: Vec<_> = (0..CONCURRENCY)
+            .map(|_| {
+                let q = queue.clone();
+                thread::spawn(move || {
+                    for i in 0..COUNT {
+                        q.push(i);
+                    }
+                })
+            })
+            .collect();
+
+        let consumers: Vec<_> = (0..CONCURRENCY)
+            .map(|_| {
+                let q = queue.clone();
+                thread::spawn(move || {
+                    let mut sum = 0;
+                    for _ in 0..COUNT {
+                        loop {
+                            if let Some(x) = q.pop() {
+                                sum += x;
+                                break;
+                            }
+                            thread::yield_now();
+                        }
+                    }
+                    sum
+                })
+            })
+            .collect();
+
+        for th in producers {
+            th.join().unwrap();
+        }
+
+        let sum: usize = consumers.into_iter().map(|th| th.join().unwrap()).sum();
+        assert_eq!(sum, CONCURRENCY * COUNT * (COUNT - 1) / 2);
+        assert!(queue.pop().is_none());
+    }
+
+    check::<Spin>();
+    check::<Backoff>();
+    check::<Yield>();
+    check::<ParkAfter<0>>();
+    check::<ParkAfter<100>>();
+}
+
+// cargo test --package lf-queue --test bounded -- test_debug --exact --nocapture
+#[test]
+fn test_debug() {
+    // The items aren't required to implement `Debug`, as they're never reported.
+    struct Item;
+
+    let queue: BoundedQueue<Item> = BoundedQueue::new(3);
+    assert_eq!(
+        format!("{:?}", queue),
+        "BoundedQueue { len: 0, capacity: 3, closed: false }"
+    );
+
+    queue.push(Item);
+    queue.push(Item);
+    assert!(queue.close());
+
+    assert_eq!(
+        format!("{:?}", queue),
+        "BoundedQueue { len: 2, capacity: 3, closed: true }"
+    );
+
+    // Wraps around the ring, so the length is computed across its end.
+    let queue: BoundedQueue<usize> = BoundedQueue::new(3);
+    for i in 0..2 {
+        queue.push(i);
+        assert_eq!(queue.pop(), Some(i));
+    }
+    for i in 0..3 {
+        queue.push(i);
+    }
+    assert_eq!(
+        format!("{:?}", queue),
+        "BoundedQueue { len: 3, capacity: 3, closed: false }"
+    );
+
+    assert_eq!(queue.pop(), Some(0));
+    assert_eq!(
+        format!("{:?}", queue),
+        "BoundedQueue { len: 2, capacity: 3, closed: false }"
+    );
+}
@@ -0,0 +1,188 @@
+#![cfg(feature = "std")]
+
+use lf_queue::{channel, RecvError, RecvTimeoutError, TryRecvError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// cargo test --package lf-queue --test channel -- test_spsc --exact --nocapture
+#[test]
+fn test_spsc() {
+    const COUNT: usize = 1_000;
+    let (tx, rx) = channel();
+
+    let th = thread::spawn(move || {
+        for i in 0..COUNT {
+            tx.send(i).unwrap();
+        }
+    });
+
+    for i in 0..COUNT {
+        assert_eq!(rx.recv(), Ok(i));
+    }
+
+    th.join().unwrap();
+    assert_eq!(rx.recv(), Err(RecvError));
+}
+
+// cargo test --package lf-queue --test channel -- test_mpmc --exact --nocapture
+#[test]
+fn test_mpmc() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    let (tx, rx) = channel::<usize>();
+    let items = Arc::new((0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>());
+
+    let consumers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let rx = rx.clone();
+            let its = items.clone();
+            thread::spawn(move || {
+                for i in rx {
+                    its[i].fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        })
+        .collect();
+
+    let producers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    tx.send(i).unwrap();
+                }
+            })
+        })
+        .collect();
+
+    // Consumers only stop once every sender is dropped.
+    drop(tx);
+
+    for th in producers.into_iter().chain(consumers) {
+        th.join().unwrap();
+    }
+
+    for c in &*items {
+        assert_eq!(c.load(Ordering::SeqCst), CONCURRENCY);
+    }
+
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+// cargo test --package lf-queue --test channel -- test_senders_dropped --exact --nocapture
+#[test]
+fn test_senders_dropped() {
+    let (tx, rx) = channel();
+    let tx2 = tx.clone();
+
+    tx.send(1).unwrap();
+    drop(tx);
+    assert_eq!(rx.try_recv(), Ok(1));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+    tx2.send(2).unwrap();
+    drop(tx2);
+    assert_eq!(rx.recv(), Ok(2));
+    assert_eq!(rx.recv(), Err(RecvError));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(10)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
+
+// cargo test --package lf-queue --test channel -- test_senders_dropped_wakes_receivers --exact --nocapture
+#[test]
+fn test_senders_dropped_wakes_receivers() {
+    let (tx, rx) = channel::<usize>();
+
+    let ths: Vec<_> = (0..4)
+        .map(|_| {
+            let rx = rx.clone();
+            thread::spawn(move || rx.recv())
+        })
+        .collect();
+
+    thread::sleep(Duration::from_millis(10));
+    drop(tx);
+
+    for th in ths {
+        assert_eq!(th.join().unwrap(), Err(RecvError));
+    }
+}
+
+// cargo test --package lf-queue --test channel -- test_receivers_dropped --exact --nocapture
+#[test]
+fn test_receivers_dropped() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (tx, rx) = channel();
+    let rx2 = rx.clone();
+
+    tx.send(DropCounter).unwrap();
+    tx.send(DropCounter).unwrap();
+
+    drop(rx);
+    assert!(tx.send(DropCounter).is_ok());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+
+    // The last receiver drops the items nobody is going to receive.
+    drop(rx2);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+
+    let item = tx.send(DropCounter).unwrap_err().0;
+    drop(item);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 4);
+}
+
+// cargo test --package lf-queue --test channel -- test_recv_timeout --exact --nocapture
+#[test]
+fn test_recv_timeout() {
+    const TIMEOUT: Duration = Duration::from_millis(20);
+    let (tx, rx) = channel();
+
+    let start = Instant::now();
+    assert_eq!(rx.recv_timeout(TIMEOUT), Err(RecvTimeoutError::Timeout));
+    assert!(start.elapsed() >= TIMEOUT);
+
+    let th = thread::spawn(move || {
+        thread::sleep(TIMEOUT);
+        tx.send(1).unwrap();
+    });
+
+    assert_eq!(rx.recv_timeout(Duration::from_secs(10)), Ok(1));
+    th.join().unwrap();
+
+    let deadline = Instant::now() + TIMEOUT;
+    assert_eq!(
+        rx.recv_deadline(deadline),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
+
+// cargo test --package lf-queue --test channel -- test_try_iter --exact --nocapture
+#[test]
+fn test_try_iter() {
+    let (tx, rx) = channel();
+
+    for i in 0..3 {
+        tx.send(i).unwrap();
+    }
+
+    assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert_eq!(rx.try_iter().next(), None);
+
+    tx.send(3).unwrap();
+    drop(tx);
+    assert_eq!((&rx).into_iter().collect::<Vec<_>>(), vec![3]);
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "latency")]
+
+use lf_queue::{LatencyHistogram, PushError, TimedQueue};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// cargo test --features latency --package lf-queue --test latency -- test_histogram_small_latencies --exact --nocapture
+#[test]
+fn test_histogram_small_latencies() {
+    let histogram = LatencyHistogram::new();
+    assert_eq!(histogram.count(), 0);
+    assert_eq!(histogram.percentile(50.0), None);
+    assert_eq!(histogram.max(), None);
+
+    // Latencies up to 16ns have their own bucket.
+    for nanos in 1..=16 {
+        histogram.record(Duration::from_nanos(nanos));
+    }
+
+    assert_eq!(histogram.count(), 16);
+    assert_eq!(histogram.percentile(0.0), Some(Duration::from_nanos(1)));
+    assert_eq!(histogram.percentile(50.0), Some(Duration::from_nanos(8)));
+    assert_eq!(histogram.percentile(75.0), Some(Duration::from_nanos(12)));
+    assert_eq!(histogram.percentile(100.0), Some(Duration::from_nanos(16)));
+    assert_eq!(histogram.max(), Some(Duration::from_nanos(16)));
+}
+
+// cargo test --features latency --package lf-queue --test latency -- test_histogram_relative_error --exact --nocapture
+#[test]
+fn test_histogram_relative_error() {
+    let histogram = LatencyHistogram::new();
+
+    for nanos in [17, 1_000, 123_456, 10_000_000, 3_000_000_000, u64::MAX / 3] {
+        histogram.reset();
+        histogram.record(Duration::from_nanos(nanos));
+        histogram.record(Duration::from_nanos(u64::MAX));
+
+        // The percentile is reported as the upper bound of the bucket, which is at
+        // most 12.5% longer than the latency.
+        let p50 = histogram.percentile(50.0).unwrap().as_nanos();
+        assert!(p50 >= u128::from(nanos), "{} < {}", p50, nanos);
+        assert!(p50 <= u128::from(nanos) * 9 / 8, "{} > {}", p50, nanos);
+    }
+
+    assert_eq!(histogram.max(), Some(Duration::from_nanos(u64::MAX)));
+    assert_eq!(
+        histogram.percentile(100.0),
+        Some(Duration::from_nanos(u64::MAX))
+    );
+}
+
+// cargo test --features latency --package lf-queue --test latency -- test_histogram_reset --exact --nocapture
+#[test]
+fn test_histogram_reset() {
+    let histogram = LatencyHistogram::new();
+    histogram.record(Duration::from_millis(10));
+    histogram.reset();
+
+    assert_eq!(histogram.count(), 0);
+    assert_eq!(histogram.percentile(99.0), None);
+    assert_eq!(histogram.max(), None);
+
+    histogram.record(Duration::from_micros(10));
+    assert_eq!(histogram.max(), Some(Duration::from_micros(10)));
+}
+
+// cargo test --features latency --package lf-queue --test latency -- test_timed_queue --exact --nocapture
+#[test]
+fn test_timed_queue() {
+    let queue: TimedQueue<usize, 7> = TimedQueue::new();
+    queue.push_batch(0..10);
+    queue.push(10);
+    assert_eq!(queue.len(), 11);
+
+    thread::sleep(Duration::from_millis(5));
+
+    for i in 0..5 {
+        assert_eq!(queue.pop(), Some(i));
+    }
+    assert_eq!(queue.try_pop(), Ok(5));
+    assert_eq!(queue.pop_timeout(Duration::from_millis(10)), Ok(6));
+    assert_eq!(queue.pop_blocking(), Some(7));
+    let deadline = Instant::now() + Duration::from_millis(10);
+    assert_eq!(queue.pop_deadline(deadline), Ok(8));
+
+    // Each item of a batch is recorded.
+    let mut buf = Vec::new();
+    assert_eq!(queue.pop_batch(&mut buf, 1), 1);
+    assert_eq!(buf, [9]);
+
+    let latency = queue.latency();
+    assert_eq!(latency.count(), 10);
+    assert!(latency.percentile(0.0).unwrap() >= Duration::from_millis(5));
+    assert!(latency.max().unwrap() >= Duration::from_millis(5));
+
+    // Items left in the queue aren't recorded.
+    assert!(queue.close());
+    assert_eq!(queue.try_push(11), Err(PushError::Closed(11)));
+    assert_eq!(queue.latency().count(), 10);
+
+    assert_eq!(queue.pop_batch(&mut buf, 10), 1);
+    assert_eq!(buf, [9, 10]);
+    assert_eq!(queue.latency().count(), 11);
+}
+
+// cargo test --features latency --package lf-queue --test latency -- test_mpmc --exact --nocapture
+#[test]
+fn test_mpmc() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    let queue: TimedQueue<usize, 7> = TimedQueue::new();
+
+    let ths: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    q.push(i);
+                }
+            })
+        })
+        .chain((0..CONCURRENCY).map(|_| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                for _ in 0..COUNT {
+                    while q.pop().is_none() {}
+                }
+            })
+        }))
+        .collect();
+
+    for th in ths {
+        th.join().unwrap();
+    }
+
+    // Every handle shares the same histogram.
+    assert_eq!(queue.latency().count(), (CONCURRENCY * COUNT) as u64);
+    assert!(queue.latency().percentile(50.0) <= queue.latency().percentile(99.0));
+}
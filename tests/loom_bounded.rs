@@ -0,0 +1,279 @@
+#![cfg(loom)]
+
+use lf_queue::{BoundedQueue, PushError};
+use loom::thread;
+
+// Below tests use a capacity lower than the item count, so producers run into a full
+// queue and slots are reused during the next lap.
+//
+// Run all tests:
+//
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_bounded --release
+//
+// Note that running some of these tests may take a few seconds. Add `LOOM_MAX_PREEMPTIONS=2` (or =3) to the command
+// above to reduce the test complexity and so its duration.
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_bounded --release -- test_mpsc --exact
+#[test]
+fn test_mpsc() {
+    loom::model(|| {
+        const COUNT: usize = 3;
+        let queue: BoundedQueue<usize> = BoundedQueue::new(COUNT);
+
+        // Moves the head and tail indexes forward, so the items below wrap around
+        // the end of the ring.
+        queue.push(0);
+        assert!(queue.pop().is_some());
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            for i in 0..2 {
+                q1.push(i);
+            }
+        });
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || q2.push(2));
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+
+        for _ in 0..COUNT {
+            assert!(queue.pop().is_some());
+        }
+
+        assert!(queue.pop().is_none());
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_bounded --release -- test_spmc --exact
+#[test]
+fn test_spmc() {
+    loom::model(|| {
+        const COUNT: usize = 3;
+        let queue: BoundedQueue<usize> = BoundedQueue::new(COUNT);
+
+        // Moves the head and tail indexes forward, so the items below wrap around
+        // the end of the ring.
+        queue.push(0);
+        assert!(queue.pop().is_some());
+
+        for i in 0..COUNT {
+            queue.push(i);
+        }
+
+        let mut n = 0;
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            let mut x = 0;
+            while q1.pop().is_some() {
+                x += 1;
+            }
+
+            x
+        });
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || {
+            let mut x = 0;
+            while q2.pop().is_some() {
+                x += 1;
+            }
+
+            x
+        });
+
+        n += th1.join().unwrap();
+        n += th2.join().unwrap();
+
+        assert_eq!(n, COUNT);
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_bounded --release -- test_try_push --exact
+#[test]
+fn test_try_push() {
+    loom::model(|| {
+        let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || q1.try_push(1));
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || q2.try_push(2));
+
+        let r1 = th1.join().unwrap();
+        let r2 = th2.join().unwrap();
+
+        // Only one of the items fits into the queue.
+        match (r1, r2) {
+            (Ok(()), Err(PushError::Full(2))) => assert_eq!(queue.pop(), Some(1)),
+            (Err(PushError::Full(1)), Ok(())) => assert_eq!(queue.pop(), Some(2)),
+            _ => panic!("exactly one item should be pushed"),
+        }
+
+        assert!(queue.pop().is_none());
+    });
+}
+
+// Loom reports a deadlock if a producer stays parked while all the other threads are
+// done, so below test also checks that no wakeup is lost.
+//
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_bounded --release -- test_push_blocking --exact
+#[test]
+fn test_push_blocking() {
+    loom::model(|| {
+        const COUNT: usize = 3;
+        let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            for i in 0..COUNT {
+                q1.push(i);
+            }
+        });
+
+        let mut i = 0;
+        while i < COUNT {
+            match queue.pop() {
+                Some(x) => {
+                    assert_eq!(x, i);
+                    i += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+
+        th1.join().unwrap();
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_bounded --release -- test_close --exact
+#[test]
+fn test_close() {
+    loom::model(|| {
+        let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+        queue.push(0);
+
+        // The producer is blocked on a full queue until the queue is closed, unless
+        // the item is popped first.
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || q1.push(1));
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || {
+            let popped = q2.pop();
+            assert!(q2.close());
+            popped
+        });
+
+        th1.join().unwrap();
+        let popped = th2.join().unwrap();
+
+        assert_eq!(queue.try_push(2), Err(PushError::Closed(2)));
+
+        // Either the item was pushed before the queue was closed, or it was dropped.
+        let mut items: Vec<_> = popped.into_iter().collect();
+        items.extend(queue.pop());
+        items.extend(queue.pop());
+        assert!(items == vec![0] || items == vec![0, 1]);
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_bounded --release -- test_force_push --exact
+#[test]
+fn test_force_push() {
+    loom::model(|| {
+        let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+        queue.push(0);
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || q1.force_push(1));
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || q2.pop());
+
+        let overwritten = th1.join().unwrap().unwrap();
+        let popped = th2.join().unwrap();
+
+        // Every item is either overwritten, popped, or still in the queue.
+        let mut items: Vec<_> = overwritten.into_iter().chain(popped).collect();
+        items.extend(queue.pop());
+        items.sort_unstable();
+
+        assert_eq!(items, vec![0, 1]);
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_bounded --release -- test_close_force_push --exact
+#[test]
+fn test_close_force_push() {
+    loom::model(|| {
+        let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+        queue.push(0);
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || q1.force_push(1));
+
+        // Without any consumer, the state of the queue can't change once it's closed.
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || {
+            assert!(q2.close());
+            format!("{:?}", q2)
+        });
+
+        let pushed = th1.join().unwrap();
+        let closed = th2.join().unwrap();
+        assert_eq!(format!("{:?}", queue), closed);
+
+        // Every item is either popped, overwritten, or rejected.
+        let mut items: Vec<_> = queue.pop().into_iter().collect();
+        match pushed {
+            Ok(overwritten) => items.extend(overwritten),
+            Err(err) => items.push(err.into_inner()),
+        }
+        items.sort_unstable();
+
+        assert_eq!(items, vec![0, 1]);
+        assert_eq!(queue.pop(), None);
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_bounded --release -- test_drop_remaining_items --exact
+#[test]
+fn test_drop_remaining_items() {
+    use loom::sync::atomic::{AtomicUsize, Ordering};
+    use loom::sync::Arc;
+
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    loom::model(|| {
+        const COUNT: usize = 3;
+        let drops = Arc::new(AtomicUsize::new(0));
+        let queue: BoundedQueue<DropCounter> = BoundedQueue::new(2);
+
+        let q1 = queue.clone();
+        let d1 = drops.clone();
+        let th1 = thread::spawn(move || {
+            for _ in 0..COUNT {
+                drop(q1.force_push(DropCounter(d1.clone())));
+            }
+        });
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || drop(q2.pop()));
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+        drop(queue);
+
+        assert_eq!(drops.load(Ordering::SeqCst), COUNT);
+    });
+}
@@ -0,0 +1,50 @@
+#![cfg(loom)]
+
+use lf_queue::{channel, RecvError};
+use loom::thread;
+
+// Loom reports a deadlock if a receiver stays parked while all the other threads are
+// done, so below tests also check that the disconnection wakes the receivers up.
+//
+// Run all tests:
+//
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_channel --release
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_channel --release -- test_senders_dropped --exact
+#[test]
+fn test_senders_dropped() {
+    loom::model(|| {
+        let (tx, rx) = channel();
+        let tx2 = tx.clone();
+
+        let th1 = thread::spawn(move || tx.send(1).unwrap());
+        let th2 = thread::spawn(move || tx2.send(2).unwrap());
+
+        let n: usize = rx.iter().sum();
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+
+        assert_eq!(n, 3);
+        assert_eq!(rx.recv(), Err(RecvError));
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_channel --release -- test_receivers_dropped --exact
+#[test]
+fn test_receivers_dropped() {
+    loom::model(|| {
+        let (tx, rx) = channel();
+        let rx2 = rx.clone();
+
+        let th1 = thread::spawn(move || rx.recv());
+        let th2 = thread::spawn(move || drop(rx2));
+
+        tx.send(1).unwrap();
+
+        assert_eq!(th1.join().unwrap(), Ok(1));
+        th2.join().unwrap();
+
+        assert!(tx.send(2).is_err());
+    });
+}
@@ -1,9 +1,9 @@
 #![cfg(loom)]
 
-use lf_queue::Queue;
+use lf_queue::{ParkAfter, Queue, Spin, TryPopError, WaitStrategy, DEFAULT_NODE_CAPACITY};
 use loom::thread;
 
-// When using the `--cfg loom` flag, the node container is equal to 4. Below test uses an item count equal to 5
+// When using the `--cfg loom` flag, nodes hold 3 items by default. Below test uses an item count equal to 5
 // to test both node addition and removal.
 //
 // Run all tests:
@@ -119,3 +119,470 @@ fn test_concurrent_push_and_pop() {
         th2.join().unwrap();
     });
 }
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_try_pop --exact
+#[test]
+fn test_try_pop() {
+    loom::model(|| {
+        const COUNT: usize = 5;
+        let queue: Queue<usize> = Queue::new();
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            for i in 0..COUNT {
+                q1.push(i);
+            }
+        });
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || {
+            for i in 0..COUNT {
+                loop {
+                    match q2.try_pop() {
+                        Ok(x) => {
+                            assert_eq!(x, i);
+                            break;
+                        }
+                        // Unlike `pop`, `try_pop` never waits for the producer,
+                        // so we yield to let it make progress.
+                        Err(TryPopError::Empty) | Err(TryPopError::Busy) => thread::yield_now(),
+                        Err(TryPopError::Closed) => unreachable!(),
+                    }
+                }
+            }
+        });
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+
+        assert_eq!(queue.try_pop(), Err(TryPopError::Empty));
+    });
+}
+
+// Drained nodes are kept in the pool and installed again by the producer, while the
+// consumer might still be peeking into them.
+//
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_node_reuse --exact
+#[test]
+fn test_node_reuse() {
+    loom::model(|| {
+        const COUNT: usize = 7;
+        let queue: Queue<usize> = Queue::with_pool_capacity(1);
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            for i in 0..COUNT {
+                q1.push(i);
+            }
+        });
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || {
+            let mut i = 0;
+            while i < COUNT {
+                match q2.try_pop() {
+                    Ok(x) => {
+                        assert_eq!(x, i);
+                        i += 1;
+                    }
+                    Err(TryPopError::Empty) | Err(TryPopError::Busy) => thread::yield_now(),
+                    Err(TryPopError::Closed) => unreachable!(),
+                }
+            }
+        });
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+
+        assert_eq!(queue.try_pop(), Err(TryPopError::Empty));
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_try_pop_and_pop --exact
+#[test]
+fn test_try_pop_and_pop() {
+    loom::model(|| {
+        const COUNT: usize = 5;
+        let queue: Queue<usize> = Queue::new();
+
+        for i in 0..COUNT {
+            queue.push(i);
+        }
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            let mut x = 0;
+            loop {
+                match q1.try_pop() {
+                    Ok(_) => x += 1,
+                    Err(TryPopError::Empty) => return x,
+                    Err(TryPopError::Busy) => thread::yield_now(),
+                    Err(TryPopError::Closed) => unreachable!(),
+                }
+            }
+        });
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || {
+            let mut x = 0;
+            while q2.pop().is_some() {
+                x += 1;
+            }
+
+            x
+        });
+
+        let n = th1.join().unwrap() + th2.join().unwrap();
+
+        assert_eq!(n, COUNT);
+    });
+}
+
+// Loom reports a deadlock if a consumer stays parked while all the other threads are
+// done, so below tests also check that no wakeup is lost.
+//
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_pop_blocking --exact
+#[test]
+fn test_pop_blocking() {
+    loom::model(|| {
+        const COUNT: usize = 4;
+        let queue: Queue<usize> = Queue::new();
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            for i in 0..COUNT {
+                q1.push(i);
+            }
+        });
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || {
+            for i in 0..COUNT {
+                assert_eq!(q2.pop_blocking(), Some(i));
+            }
+        });
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+
+        assert!(queue.pop().is_none());
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_pop_blocking_multi_consumer --exact
+#[test]
+fn test_pop_blocking_multi_consumer() {
+    loom::model(|| {
+        let queue: Queue<usize> = Queue::new();
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || q1.pop_blocking());
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || q2.pop_blocking());
+
+        queue.push(1);
+        queue.push(2);
+
+        let n = th1.join().unwrap().unwrap() + th2.join().unwrap().unwrap();
+
+        assert_eq!(n, 3);
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_close --exact
+#[test]
+fn test_close() {
+    loom::model(|| {
+        let queue: Queue<usize> = Queue::new();
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            let pushed = q1.try_push(1).is_ok();
+            q1.close();
+            pushed
+        });
+
+        // The consumer is only woken up by the close once it popped the item.
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || {
+            let mut n = 0;
+            while q2.pop_blocking().is_some() {
+                n += 1;
+            }
+
+            n
+        });
+
+        assert!(th1.join().unwrap());
+        assert_eq!(th2.join().unwrap(), 1);
+        assert!(queue.pop_blocking().is_none());
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_close_concurrent_push --exact
+#[test]
+fn test_close_concurrent_push() {
+    loom::model(|| {
+        let queue: Queue<usize> = Queue::new();
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || q1.try_push(1).is_ok());
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || q2.close());
+
+        let pushed = th1.join().unwrap();
+        assert!(th2.join().unwrap());
+
+        // An item is either pushed before the queue is closed, or rejected.
+        if pushed {
+            assert_eq!(queue.pop(), Some(1));
+        }
+
+        assert_eq!(queue.try_pop(), Err(TryPopError::Closed));
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_drop_remaining_items --exact
+#[test]
+fn test_drop_remaining_items() {
+    use loom::sync::atomic::{AtomicUsize, Ordering};
+    use loom::sync::Arc;
+
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    loom::model(|| {
+        const COUNT: usize = 5;
+        let drops = Arc::new(AtomicUsize::new(0));
+        let queue: Queue<DropCounter> = Queue::new();
+
+        let q1 = queue.clone();
+        let d1 = drops.clone();
+        let th1 = thread::spawn(move || {
+            for _ in 0..COUNT {
+                q1.push(DropCounter(d1.clone()));
+            }
+        });
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || while q2.pop().is_some() {});
+
+        drop(queue);
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+
+        // Each handle has been dropped, so the items that haven't been popped
+        // must have been dropped alongside the queue.
+        assert_eq!(drops.load(Ordering::SeqCst), COUNT);
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_push_batch --exact
+#[test]
+fn test_push_batch() {
+    loom::model(|| {
+        let queue: Queue<usize> = Queue::new();
+
+        // Depending on which batch is pushed first, it either fills the first node or
+        // spans over both nodes.
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || q1.push_batch(0..2));
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || q2.push_batch(2..5));
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+
+        let mut items: Vec<_> = (0..5).map(|_| queue.pop().unwrap()).collect();
+        assert!(queue.pop().is_none());
+
+        items.sort_unstable();
+        assert_eq!(items, vec![0, 1, 2, 3, 4]);
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_pop_batch --exact
+#[test]
+fn test_pop_batch() {
+    loom::model(|| {
+        const COUNT: usize = 5;
+        let queue: Queue<usize> = Queue::new();
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            for i in 0..COUNT {
+                q1.push(i);
+            }
+        });
+
+        // Pops runs of items while they're being pushed, and the next node installed.
+        let mut items = Vec::new();
+        while items.len() < COUNT {
+            if queue.pop_batch(&mut items, COUNT) == 0 {
+                thread::yield_now();
+            }
+        }
+
+        th1.join().unwrap();
+
+        assert_eq!(items, vec![0, 1, 2, 3, 4]);
+        assert!(queue.pop().is_none());
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_pop_batch_concurrent --exact
+#[test]
+fn test_pop_batch_concurrent() {
+    loom::model(|| {
+        const COUNT: usize = 5;
+        let queue: Queue<usize> = Queue::new();
+        queue.push_batch(0..COUNT);
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            let mut items = Vec::new();
+            let _ = q1.pop_batch(&mut items, 2);
+            items
+        });
+
+        let mut items = Vec::new();
+        let _ = queue.pop_batch(&mut items, 2);
+
+        items.extend(th1.join().unwrap());
+        while let Some(item) = queue.pop() {
+            items.push(item);
+        }
+
+        items.sort_unstable();
+        assert_eq!(items, vec![0, 1, 2, 3, 4]);
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_len --exact
+#[test]
+fn test_len() {
+    loom::model(|| {
+        const COUNT: usize = 4;
+        let queue: Queue<usize> = Queue::new();
+        queue.push(0);
+
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            for i in 1..COUNT {
+                q1.push(i);
+            }
+        });
+
+        // The length is always within the number of items pushed and popped so far.
+        assert!(queue.pop().is_some());
+        let len = queue.len();
+        assert!(len < COUNT);
+        assert_eq!(queue.is_empty(), len == 0);
+
+        th1.join().unwrap();
+
+        assert_eq!(queue.len(), COUNT - 1);
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_get_mut --exact
+#[test]
+fn test_get_mut() {
+    loom::model(|| {
+        const COUNT: usize = 5;
+        let mut queue: Queue<usize> = Queue::new();
+
+        // Fills two nodes and pops from the first one with exclusive access.
+        let mut q = queue.get_mut().unwrap();
+        for i in 0..COUNT {
+            q.push(i);
+        }
+        assert_eq!(q.pop(), Some(0));
+
+        // The remaining items are popped concurrently, draining both nodes.
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || {
+            let mut items = Vec::new();
+            for _ in 0..2 {
+                items.extend(q1.pop());
+            }
+            items
+        });
+
+        let mut items: Vec<_> = queue.try_iter().collect();
+        items.extend(th1.join().unwrap());
+        items.extend(queue.try_into_vec().unwrap());
+
+        items.sort_unstable();
+        assert_eq!(items, vec![1, 2, 3, 4]);
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_first_node --exact
+#[test]
+fn test_first_node() {
+    loom::model(|| {
+        let queue: Queue<usize> = Queue::new();
+
+        // Both producers race to install the first node, while a consumer looks for an
+        // item.
+        let q1 = queue.clone();
+        let th1 = thread::spawn(move || q1.push(1));
+
+        let q2 = queue.clone();
+        let th2 = thread::spawn(move || q2.push(2));
+
+        let mut items: Vec<_> = queue.pop().into_iter().collect();
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+
+        items.extend(queue.try_iter());
+        items.sort_unstable();
+        assert_eq!(items, vec![1, 2]);
+    });
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --package lf-queue --test loom_queue --release -- test_wait_strategy --exact
+#[test]
+fn test_wait_strategy() {
+    fn check<W: WaitStrategy + 'static>() {
+        loom::model(|| {
+            const COUNT: usize = 4;
+            let queue: Queue<usize, DEFAULT_NODE_CAPACITY, W> = Queue::new();
+
+            let q1 = queue.clone();
+            let th1 = thread::spawn(move || {
+                for i in 0..COUNT {
+                    q1.push(i);
+                }
+            });
+
+            // Pops while the items are being written, and the next node installed.
+            let mut i = 0;
+            while i < COUNT {
+                match queue.pop() {
+                    Some(x) => {
+                        assert_eq!(x, i);
+                        i += 1;
+                    }
+                    None => thread::yield_now(),
+                }
+            }
+
+            th1.join().unwrap();
+        });
+    }
+
+    check::<Spin>();
+    check::<ParkAfter<1>>();
+}
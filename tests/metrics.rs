@@ -0,0 +1,167 @@
+#![cfg(feature = "metrics")]
+
+use lf_queue::{Queue, QueueStats, RawQueue, WaitStrategy};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+// cargo test --features metrics --package lf-queue --test metrics -- test_operations --exact --nocapture
+#[test]
+fn test_operations() {
+    let queue: Queue<usize, 7> = Queue::new();
+    assert_eq!(queue.stats(), QueueStats::default());
+
+    for i in 0..10 {
+        queue.push(i);
+    }
+    queue.push_batch(10..20);
+
+    for _ in 0..5 {
+        assert!(queue.pop().is_some());
+    }
+    assert!(queue.try_pop().is_ok());
+    let mut buf = Vec::new();
+    assert_eq!(queue.pop_batch(&mut buf, 20), 14);
+
+    assert_eq!(queue.pop(), None);
+    assert!(queue.try_pop().is_err());
+    assert_eq!(queue.pop_batch(&mut buf, 20), 0);
+
+    let stats = queue.stats();
+    assert_eq!(stats.pushes, 20);
+    assert_eq!(stats.pops, 20);
+    assert_eq!(stats.empty_pops, 3);
+
+    // A single thread never contends with another one.
+    assert_eq!(stats.tail_cas_failures, 0);
+    assert_eq!(stats.head_cas_failures, 0);
+    assert_eq!(stats.waits, 0);
+}
+
+// cargo test --features metrics --package lf-queue --test metrics -- test_nodes --exact --nocapture
+#[test]
+fn test_nodes() {
+    // Without any pool, drained nodes are freed right away.
+    let queue: Queue<usize, 7> = Queue::with_pool_capacity(0);
+    queue.push_batch(0..14);
+    while queue.pop().is_some() {}
+
+    let stats = queue.stats();
+    assert_eq!(stats.nodes_allocated, 3);
+    assert_eq!(stats.nodes_freed, 2);
+
+    // Otherwise, they're kept until the pool is cleared.
+    let queue: Queue<usize, 7> = Queue::new();
+    queue.push_batch(0..14);
+    while queue.pop().is_some() {}
+    queue.push_batch(0..14);
+    while queue.pop().is_some() {}
+
+    let stats = queue.stats();
+    assert_eq!(stats.nodes_allocated, 3);
+    assert_eq!(stats.nodes_freed, 0);
+
+    queue.shrink_to_fit();
+    assert_eq!(queue.stats().nodes_freed, 2);
+}
+
+// cargo test --features metrics --package lf-queue --test metrics -- test_get_mut --exact --nocapture
+#[test]
+fn test_get_mut() {
+    let mut queue: RawQueue<usize, 7> = RawQueue::new();
+
+    let mut q = queue.get_mut();
+    for i in 0..10 {
+        q.push(i);
+    }
+    while q.pop().is_some() {}
+
+    let stats = queue.stats();
+    assert_eq!(stats.pushes, 10);
+    assert_eq!(stats.pops, 10);
+    assert_eq!(stats.empty_pops, 1);
+    assert_eq!(stats.nodes_allocated, 2);
+}
+
+// cargo test --features metrics --package lf-queue --test metrics -- test_mpmc --exact --nocapture
+#[test]
+fn test_mpmc() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    let queue: RawQueue<usize, 7> = RawQueue::new();
+
+    thread::scope(|s| {
+        for _ in 0..CONCURRENCY {
+            let _ = s.spawn(|| {
+                for i in 0..COUNT {
+                    queue.push(i);
+                }
+            });
+            let _ = s.spawn(|| {
+                for _ in 0..COUNT {
+                    while queue.pop().is_none() {}
+                }
+            });
+        }
+    });
+
+    // Every thread records its operations into its own shard, which are all summed up.
+    let stats = queue.stats();
+    assert_eq!(stats.pushes, (CONCURRENCY * COUNT) as u64);
+    assert_eq!(stats.pops, (CONCURRENCY * COUNT) as u64);
+    assert!(stats.nodes_allocated >= stats.nodes_freed);
+}
+
+// cargo test --features metrics --package lf-queue --test metrics -- test_waits --exact --nocapture
+#[test]
+fn test_waits() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+
+    // Counts every wait of the producers and the consumers.
+    static WAITS: AtomicU64 = AtomicU64::new(0);
+
+    struct Counting;
+
+    impl WaitStrategy for Counting {
+        fn wait(_step: u32) {
+            WAITS.fetch_add(1, Ordering::SeqCst);
+            thread::yield_now();
+        }
+    }
+
+    // Single item nodes make the producers wait for the next node to be installed.
+    let queue: RawQueue<usize, 1, Counting> = RawQueue::new();
+
+    thread::scope(|s| {
+        for _ in 0..CONCURRENCY {
+            let _ = s.spawn(|| {
+                for i in 0..COUNT {
+                    queue.push(i);
+                }
+            });
+            let _ = s.spawn(|| {
+                for _ in 0..COUNT {
+                    while queue.pop().is_none() {}
+                }
+            });
+        }
+    });
+
+    assert_eq!(queue.stats().waits, WAITS.load(Ordering::SeqCst));
+
+    // Without consumers, every wait comes from a producer.
+    let queue: RawQueue<usize, 1, Counting> = RawQueue::new();
+    WAITS.store(0, Ordering::SeqCst);
+
+    thread::scope(|s| {
+        for _ in 0..CONCURRENCY {
+            let _ = s.spawn(|| {
+                for i in 0..COUNT {
+                    queue.push(i);
+                }
+            });
+        }
+    });
+
+    assert_eq!(queue.stats().waits, WAITS.load(Ordering::SeqCst));
+}
@@ -0,0 +1,72 @@
+// Uses the crate from a `#![no_std]` crate, only relying on `core` and `alloc`.
+//
+// cargo test --no-default-features --package lf-queue --test no_std
+#![cfg(not(loom))]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use lf_queue::{BoundedQueue, PushError, Queue, RawQueue, Spin, StaticQueue, TryPopError};
+
+// cargo test --no-default-features --package lf-queue --test no_std -- test_queue --exact --nocapture
+#[test]
+fn test_queue() {
+    let queue: Queue<usize, 7> = Queue::new();
+    assert_eq!(queue.try_pop(), Err(TryPopError::Empty));
+
+    for i in 0..20 {
+        queue.push(i);
+    }
+    assert_eq!(queue.len(), 20);
+
+    for i in 0..10 {
+        assert_eq!(queue.pop(), Some(i));
+    }
+
+    let mut buf = Vec::new();
+    assert_eq!(queue.pop_batch(&mut buf, 20), 10);
+    assert_eq!(buf, (10..20).collect::<Vec<_>>());
+
+    assert!(queue.close());
+    assert_eq!(queue.try_push(1), Err(PushError::Closed(1)));
+    assert_eq!(queue.try_pop(), Err(TryPopError::Closed));
+}
+
+// cargo test --no-default-features --package lf-queue --test no_std -- test_raw_queue --exact --nocapture
+#[test]
+fn test_raw_queue() {
+    let queue: RawQueue<usize, 7, Spin> = RawQueue::new();
+
+    queue.push_batch(0..20);
+    assert_eq!(
+        queue.drain().collect::<Vec<_>>(),
+        (0..20).collect::<Vec<_>>()
+    );
+    assert!(queue.is_empty());
+}
+
+// cargo test --no-default-features --package lf-queue --test no_std -- test_static_queue --exact --nocapture
+#[test]
+fn test_static_queue() {
+    static QUEUE: StaticQueue<usize, 7> = StaticQueue::new();
+
+    QUEUE.push_batch(0..20);
+    assert_eq!(QUEUE.pop(), Some(0));
+    assert_eq!(QUEUE.len(), 19);
+}
+
+// cargo test --no-default-features --package lf-queue --test no_std -- test_bounded_queue --exact --nocapture
+#[test]
+fn test_bounded_queue() {
+    let queue = BoundedQueue::<usize>::new(2);
+
+    assert_eq!(queue.try_push(1), Ok(()));
+    assert_eq!(queue.try_push(2), Ok(()));
+    assert_eq!(queue.try_push(3), Err(PushError::Full(3)));
+    assert_eq!(queue.force_push(3), Ok(Some(1)));
+
+    assert_eq!(queue.pop(), Some(2));
+    assert_eq!(queue.pop(), Some(3));
+    assert_eq!(queue.pop(), None);
+}
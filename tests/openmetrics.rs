@@ -0,0 +1,58 @@
+#![cfg(feature = "metrics")]
+
+// The expected outputs are stored in `tests/golden`. When the output format changes
+// on purpose, run the tests with `BLESS=1` to write the new outputs, and review them.
+
+use lf_queue::{Queue, QueueStats};
+use std::path::Path;
+use std::{env, fs};
+
+/// Checks the OpenMetrics output of `stats` against the golden file `file`.
+fn assert_golden(file: &str, stats: QueueStats, name: &str) {
+    let mut out = String::new();
+    stats.write_openmetrics(&mut out, name).unwrap();
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(file);
+    if env::var_os("BLESS").is_some() {
+        fs::write(&path, &out).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap();
+    assert_eq!(out, expected, "output differs from {}", path.display());
+}
+
+// cargo test --features metrics --package lf-queue --test openmetrics -- test_empty --exact --nocapture
+#[test]
+fn test_empty() {
+    let queue: Queue<usize, 7> = Queue::new();
+    assert_golden("openmetrics_empty.txt", queue.stats(), "empty");
+}
+
+// cargo test --features metrics --package lf-queue --test openmetrics -- test_operations --exact --nocapture
+#[test]
+fn test_operations() {
+    let queue: Queue<usize, 7> = Queue::with_pool_capacity(0);
+    queue.push_batch(0..20);
+    for _ in 0..15 {
+        assert!(queue.pop().is_some());
+    }
+    queue.push(20);
+
+    assert_golden("openmetrics_operations.txt", queue.stats(), "ingest");
+}
+
+// cargo test --features metrics --package lf-queue --test openmetrics -- test_label_escaping --exact --nocapture
+#[test]
+fn test_label_escaping() {
+    let queue: Queue<usize, 7> = Queue::new();
+    queue.push(1);
+
+    assert_golden(
+        "openmetrics_escaping.txt",
+        queue.stats(),
+        "say \"hi\"\\\nbye",
+    );
+}
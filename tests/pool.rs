@@ -0,0 +1,78 @@
+use lf_queue::Queue;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Counts the allocations made by this test binary, which only runs the test below.
+struct CountingAlloc;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+static FREES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        FREES.fetch_add(1, Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// cargo test --package lf-queue --test pool -- test_node_pool --exact --nocapture
+#[test]
+fn test_node_pool() {
+    const NODES: usize = 4;
+    const COUNT: usize = 7 * NODES;
+    let queue: Queue<usize, 7> = Queue::with_pool_capacity(NODES);
+
+    let fill = || {
+        for i in 0..COUNT {
+            queue.push(i);
+        }
+
+        for i in 0..COUNT {
+            assert_eq!(queue.pop(), Some(i));
+        }
+    };
+
+    // The first round allocates the nodes, which are then kept in the pool.
+    fill();
+
+    let allocs = ALLOCS.load(Ordering::SeqCst);
+    let frees = FREES.load(Ordering::SeqCst);
+
+    // Later rounds reuse them, without any allocation.
+    for _ in 0..10 {
+        fill();
+    }
+
+    assert_eq!(ALLOCS.load(Ordering::SeqCst), allocs);
+    assert_eq!(FREES.load(Ordering::SeqCst), frees);
+
+    // Once the pool is cleared, the nodes are freed and allocated again.
+    queue.shrink_to_fit();
+    assert_eq!(FREES.load(Ordering::SeqCst), frees + NODES);
+
+    fill();
+    assert_eq!(ALLOCS.load(Ordering::SeqCst), allocs + NODES);
+
+    // Without a pool, every drained node is freed.
+    let queue: Queue<usize, 7> = Queue::with_pool_capacity(0);
+    let allocs = ALLOCS.load(Ordering::SeqCst);
+    let frees = FREES.load(Ordering::SeqCst);
+
+    for i in 0..COUNT {
+        queue.push(i);
+    }
+    while queue.pop().is_some() {}
+
+    // The first node is only allocated by the first push, along with the nodes which
+    // follow it.
+    assert_eq!(ALLOCS.load(Ordering::SeqCst), allocs + NODES + 1);
+    assert_eq!(FREES.load(Ordering::SeqCst), frees + NODES);
+}
@@ -1,13 +1,18 @@
-use lf_queue::Queue;
+#![cfg(feature = "std")]
+
+use lf_queue::{
+    Backoff, ParkAfter, PopTimeoutError, PushError, Queue, Spin, TryPopError, WaitStrategy, Yield,
+};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant};
 
 // cargo test --package lf-queue --test queue -- test_spsc --exact --nocapture
 #[test]
 fn test_spsc() {
     const COUNT: usize = 7 * 3;
-    let queue: Queue<usize> = Queue::new();
+    let queue: Queue<usize, 7> = Queue::new();
 
     for i in 0..COUNT {
         queue.push(i);
@@ -129,3 +134,793 @@ fn test_mpmc() {
 
     assert!(queue.pop().is_none());
 }
+
+// cargo test --package lf-queue --test queue -- test_push_batch --exact --nocapture
+#[test]
+fn test_push_batch() {
+    let mut queue: Queue<usize, 7> = Queue::new();
+
+    // Runs starting at, and spanning over, node boundaries.
+    queue.push_batch(0..3);
+    queue.push_batch(3..7);
+    queue.push_batch(7..30);
+    queue.push_batch(std::iter::empty());
+    queue.extend(30..40);
+
+    for i in 0..40 {
+        assert_eq!(queue.pop(), Some(i));
+    }
+    assert!(queue.pop().is_none());
+
+    // The items are dropped once the queue is closed.
+    let drops = AtomicUsize::new(0);
+    let queue: Queue<DropCounter<'_>, 7> = Queue::new();
+    queue.close();
+    queue.push_batch((0..10).map(|_| DropCounter(&drops)).collect::<Vec<_>>());
+
+    assert_eq!(drops.load(Ordering::SeqCst), 10);
+    assert!(queue.pop().is_none());
+}
+
+// cargo test --package lf-queue --test queue -- test_push_batch_mpmc --exact --nocapture
+#[test]
+fn test_push_batch_mpmc() {
+    const COUNT: usize = 1_000;
+    const BATCH: usize = 13;
+    const CONCURRENCY: usize = 4;
+    let queue: Queue<(usize, usize), 7> = Queue::new();
+
+    let producers: Vec<_> = (0..CONCURRENCY)
+        .map(|p| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                for start in (0..COUNT).step_by(BATCH) {
+                    q.push_batch((start..COUNT.min(start + BATCH)).map(|i| (p, i)));
+                }
+            })
+        })
+        .collect();
+
+    let consumers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                let mut items = Vec::new();
+                while let Some(item) = q.pop_blocking() {
+                    items.push(item);
+                }
+                items
+            })
+        })
+        .collect();
+
+    for th in producers {
+        th.join().unwrap();
+    }
+
+    queue.close();
+
+    let mut items: Vec<_> = consumers
+        .into_iter()
+        .flat_map(|th| th.join().unwrap())
+        .collect();
+
+    // Every item has been popped once.
+    items.sort_unstable();
+    let expected: Vec<_> = (0..CONCURRENCY)
+        .flat_map(|p| (0..COUNT).map(move |i| (p, i)))
+        .collect();
+    assert_eq!(items, expected);
+}
+
+// cargo test --package lf-queue --test queue -- test_pop_batch --exact --nocapture
+#[test]
+fn test_pop_batch() {
+    let queue: Queue<usize, 7> = Queue::new();
+    let mut buf = Vec::new();
+
+    assert_eq!(queue.pop_batch(&mut buf, 10), 0);
+
+    queue.push_batch(0..30);
+    assert_eq!(queue.pop_batch(&mut buf, 0), 0);
+
+    // Runs starting at, and spanning over, node boundaries.
+    assert_eq!(queue.pop_batch(&mut buf, 3), 3);
+    assert_eq!(queue.pop_batch(&mut buf, 4), 4);
+    assert_eq!(queue.pop_batch(&mut buf, 20), 20);
+    assert_eq!(queue.pop_batch(&mut buf, 20), 3);
+    assert_eq!(queue.pop_batch(&mut buf, 20), 0);
+    assert_eq!(buf, (0..30).collect::<Vec<_>>());
+
+    // The items are appended to the buffer.
+    queue.push(30);
+    assert_eq!(queue.pop_batch(&mut buf, 20), 1);
+    assert_eq!(buf, (0..31).collect::<Vec<_>>());
+    assert!(queue.pop().is_none());
+}
+
+// cargo test --package lf-queue --test queue -- test_pop_batch_mpmc --exact --nocapture
+#[test]
+fn test_pop_batch_mpmc() {
+    const COUNT: usize = 1_000;
+    const BATCH: usize = 13;
+    const CONCURRENCY: usize = 4;
+    let queue: Queue<usize, 7> = Queue::new();
+    let done = Arc::new(AtomicUsize::new(0));
+
+    let producers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    q.push(i);
+                }
+            })
+        })
+        .collect();
+
+    let consumers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                let mut items = Vec::new();
+                loop {
+                    let finished = done.load(Ordering::SeqCst) == CONCURRENCY;
+                    if q.pop_batch(&mut items, BATCH) == 0 {
+                        if finished {
+                            break items;
+                        }
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for th in producers {
+        th.join().unwrap();
+        done.fetch_add(1, Ordering::SeqCst);
+    }
+
+    let mut items: Vec<_> = consumers
+        .into_iter()
+        .flat_map(|th| th.join().unwrap())
+        .collect();
+
+    // Every item has been popped once.
+    items.sort_unstable();
+    let mut expected: Vec<_> = (0..CONCURRENCY).flat_map(|_| 0..COUNT).collect();
+    expected.sort_unstable();
+    assert_eq!(items, expected);
+}
+
+// cargo test --package lf-queue --test queue -- test_len --exact --nocapture
+#[test]
+fn test_len() {
+    fn check<const N: usize>() {
+        const COUNT: usize = 50;
+        let queue: Queue<usize, N> = Queue::new();
+        assert_eq!(queue.len(), 0);
+        assert!(queue.is_empty());
+
+        // Fills several nodes, crossing their boundaries one item at a time.
+        for i in 0..COUNT {
+            queue.push(i);
+            assert_eq!(queue.len(), i + 1);
+            assert!(!queue.is_empty());
+        }
+
+        // Pops while the head and the tail point to different nodes, then to the same
+        // one, so the head index may or may not hold the mark bit.
+        for i in 0..COUNT / 2 {
+            assert_eq!(queue.pop(), Some(i));
+            assert_eq!(queue.len(), COUNT - i - 1);
+        }
+
+        // Pushes and pops by runs.
+        queue.push_batch(0..COUNT);
+        assert_eq!(queue.len(), COUNT + COUNT / 2);
+
+        let mut buf = Vec::new();
+        assert_eq!(queue.pop_batch(&mut buf, COUNT), COUNT);
+        assert_eq!(queue.len(), COUNT / 2);
+
+        // Closing the queue doesn't change its length.
+        queue.close();
+        assert_eq!(queue.len(), COUNT / 2);
+
+        while queue.pop().is_some() {}
+        assert_eq!(queue.len(), 0);
+        assert!(queue.is_empty());
+    }
+
+    check::<1>();
+    check::<7>();
+    check::<32>();
+}
+
+// cargo test --package lf-queue --test queue -- test_iterators --exact --nocapture
+#[test]
+fn test_iterators() {
+    // Collects the items across node boundaries.
+    let mut queue: Queue<usize, 7> = (0..20).collect();
+    assert_eq!(queue.len(), 20);
+
+    queue.extend(20..30);
+    assert_eq!(
+        queue.try_iter().take(5).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4]
+    );
+
+    // Only drains the items held when called.
+    let mut drained = Vec::new();
+    for i in queue.drain() {
+        queue.push(i + 100);
+        drained.push(i);
+    }
+    assert_eq!(drained, (5..30).collect::<Vec<_>>());
+    assert_eq!(queue.len(), 25);
+
+    // An exhausted drain stays exhausted.
+    let mut drain = queue.drain();
+    assert_eq!(drain.by_ref().count(), 25);
+    queue.push(0);
+    assert!(drain.next().is_none());
+    assert_eq!(queue.try_iter().collect::<Vec<_>>(), vec![0]);
+
+    // Iterates over the items left once the last handle is consumed.
+    queue.push_batch(0..10);
+    let q = queue.clone();
+    drop(queue);
+    assert_eq!(
+        q.into_iter().collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+}
+
+// cargo test --package lf-queue --test queue -- test_into_iter_exclusive --exact --nocapture
+#[test]
+fn test_into_iter_exclusive() {
+    const COUNT: usize = 7 * 3;
+    let queue: Queue<usize, 7> = Queue::new();
+    queue.push_batch(0..COUNT);
+    assert_eq!(queue.pop(), Some(0));
+
+    // Holding the last handle, the iterator knows how many items are left.
+    let mut iter = queue.into_iter();
+    assert_eq!(iter.size_hint(), (COUNT - 1, Some(COUNT - 1)));
+    assert_eq!(
+        iter.by_ref().take(10).collect::<Vec<_>>(),
+        (1..11).collect::<Vec<_>>()
+    );
+    assert_eq!(iter.size_hint(), (COUNT - 11, Some(COUNT - 11)));
+    assert_eq!(iter.collect::<Vec<_>>(), (11..COUNT).collect::<Vec<_>>());
+
+    // Other handles keep popping through the shared queue.
+    let queue: Queue<usize, 7> = Queue::new();
+    queue.push_batch(0..COUNT);
+    let q = queue.clone();
+    let mut iter = queue.into_iter();
+    assert_eq!(iter.size_hint(), (0, None));
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(q.pop(), Some(1));
+    assert_eq!(iter.collect::<Vec<_>>(), (2..COUNT).collect::<Vec<_>>());
+
+    // Dropping the iterator drops the items left.
+    let drops = AtomicUsize::new(0);
+    let queue: Queue<DropCounter<'_>, 7> = Queue::new();
+    queue.push_batch((0..COUNT).map(|_| DropCounter(&drops)));
+    let mut iter = queue.into_iter();
+    drop(iter.next());
+    drop(iter);
+    assert_eq!(drops.load(Ordering::SeqCst), COUNT);
+}
+
+// cargo test --package lf-queue --test queue -- test_get_mut --exact --nocapture
+#[test]
+fn test_get_mut() {
+    const COUNT: usize = 7 * 3;
+    let mut queue: Queue<usize, 7> = Queue::new();
+
+    let mut q = queue.get_mut().unwrap();
+    assert!(q.is_empty());
+    for i in 0..COUNT {
+        q.push(i);
+        assert_eq!(q.len(), i + 1);
+    }
+
+    // Stops in the middle of a node, the rest of which is popped by shared handles.
+    for i in 0..10 {
+        assert_eq!(q.pop(), Some(i));
+    }
+    assert_eq!(q.len(), COUNT - 10);
+
+    let handle = queue.clone();
+    assert!(queue.get_mut().is_none());
+
+    for i in 10..15 {
+        assert_eq!(handle.pop(), Some(i));
+    }
+    handle.push_batch(COUNT..COUNT + 5);
+    drop(handle);
+
+    // Takes over the items pushed and popped by shared handles.
+    let mut q = queue.get_mut().unwrap();
+    for i in 15..COUNT + 5 {
+        assert_eq!(q.pop(), Some(i));
+    }
+    assert_eq!(q.pop(), None);
+
+    queue.close();
+    let mut q = queue.get_mut().unwrap();
+    assert_eq!(q.try_push(1), Err(PushError::Closed(1)));
+    assert_eq!(q.pop(), None);
+}
+
+// cargo test --package lf-queue --test queue -- test_try_into_vec --exact --nocapture
+#[test]
+fn test_try_into_vec() {
+    let queue: Queue<usize, 7> = Queue::new();
+    queue.push_batch(0..20);
+    assert_eq!(queue.pop(), Some(0));
+
+    let handle = queue.clone();
+    let queue = queue.try_into_vec().unwrap_err();
+    assert_eq!(handle.pop(), Some(1));
+    drop(handle);
+
+    assert_eq!(queue.try_into_vec().unwrap(), (2..20).collect::<Vec<_>>());
+
+    // Drops the items along with the vector.
+    let drops = AtomicUsize::new(0);
+    let queue: Queue<DropCounter<'_>, 7> = Queue::new();
+    queue.push_batch((0..10).map(|_| DropCounter(&drops)));
+    drop(queue.try_into_vec());
+    assert_eq!(drops.load(Ordering::SeqCst), 10);
+}
+
+// cargo test --package lf-queue --test queue -- test_try_pop --exact --nocapture
+#[test]
+fn test_try_pop() {
+    const COUNT: usize = 7 * 3;
+    let queue: Queue<usize, 7> = Queue::new();
+
+    assert_eq!(queue.try_pop(), Err(TryPopError::Empty));
+
+    for i in 0..COUNT {
+        queue.push(i);
+    }
+
+    for i in 0..COUNT {
+        assert_eq!(queue.try_pop(), Ok(i));
+    }
+
+    assert_eq!(queue.try_pop(), Err(TryPopError::Empty));
+}
+
+// cargo test --package lf-queue --test queue -- test_try_pop_mpmc --exact --nocapture
+#[test]
+fn test_try_pop_mpmc() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    let queue: Queue<usize> = Queue::new();
+    let items = Arc::new((0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>());
+
+    let consumers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            let its = items.clone();
+            thread::spawn(move || {
+                for _ in 0..COUNT {
+                    let n = loop {
+                        match q.try_pop() {
+                            Ok(x) => break x,
+                            Err(TryPopError::Empty) => thread::yield_now(),
+                            Err(TryPopError::Busy) => {}
+                            Err(TryPopError::Closed) => unreachable!(),
+                        }
+                    };
+                    its[n].fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        })
+        .collect();
+
+    let producers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    q.push(i);
+                }
+            })
+        })
+        .collect();
+
+    for th in producers.into_iter().chain(consumers) {
+        th.join().unwrap();
+    }
+
+    for c in &*items {
+        assert_eq!(c.load(Ordering::SeqCst), CONCURRENCY);
+    }
+
+    assert_eq!(queue.try_pop(), Err(TryPopError::Empty));
+}
+
+// cargo test --package lf-queue --test queue -- test_pop_blocking --exact --nocapture
+#[test]
+fn test_pop_blocking() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    let queue: Queue<usize> = Queue::new();
+    let items = Arc::new((0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>());
+
+    let consumers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            let its = items.clone();
+            thread::spawn(move || {
+                for _ in 0..COUNT {
+                    its[q.pop_blocking().unwrap()].fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        })
+        .collect();
+
+    let producers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    q.push(i);
+                    if i % 100 == 0 {
+                        thread::sleep(Duration::from_millis(1));
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for th in producers.into_iter().chain(consumers) {
+        th.join().unwrap();
+    }
+
+    for c in &*items {
+        assert_eq!(c.load(Ordering::SeqCst), CONCURRENCY);
+    }
+
+    assert!(queue.pop().is_none());
+}
+
+// cargo test --package lf-queue --test queue -- test_pop_timeout --exact --nocapture
+#[test]
+fn test_pop_timeout() {
+    const TIMEOUT: Duration = Duration::from_millis(20);
+    let queue: Queue<usize> = Queue::new();
+
+    let start = Instant::now();
+    assert_eq!(queue.pop_timeout(TIMEOUT), Err(PopTimeoutError::Timeout));
+    assert!(start.elapsed() >= TIMEOUT);
+
+    let q = queue.clone();
+    let th = thread::spawn(move || q.pop_timeout(Duration::from_secs(10)));
+
+    thread::sleep(TIMEOUT);
+    queue.push(1);
+
+    assert_eq!(th.join().unwrap(), Ok(1));
+    assert_eq!(
+        queue.pop_timeout(Duration::ZERO),
+        Err(PopTimeoutError::Timeout)
+    );
+}
+
+// cargo test --package lf-queue --test queue -- test_pop_deadline --exact --nocapture
+#[test]
+fn test_pop_deadline() {
+    const TIMEOUT: Duration = Duration::from_millis(20);
+    let queue: Queue<usize> = Queue::new();
+
+    let deadline = Instant::now() + TIMEOUT;
+    assert_eq!(queue.pop_deadline(deadline), Err(PopTimeoutError::Timeout));
+    assert!(Instant::now() >= deadline);
+
+    queue.push(1);
+    assert_eq!(queue.pop_deadline(deadline), Ok(1));
+}
+
+// cargo test --package lf-queue --test queue -- test_close --exact --nocapture
+#[test]
+fn test_close() {
+    const COUNT: usize = 7 * 3;
+    let queue: Queue<usize, 7> = Queue::new();
+
+    for i in 0..COUNT {
+        queue.push(i);
+    }
+
+    assert!(!queue.is_closed());
+    assert!(queue.close());
+    assert!(queue.is_closed());
+    assert!(!queue.close());
+
+    assert_eq!(queue.try_push(COUNT), Err(PushError::Closed(COUNT)));
+    queue.push(COUNT);
+
+    for i in 0..COUNT {
+        assert_eq!(queue.try_pop(), Ok(i));
+    }
+
+    assert_eq!(queue.try_pop(), Err(TryPopError::Closed));
+    assert!(queue.pop().is_none());
+    assert!(queue.pop_blocking().is_none());
+    assert_eq!(
+        queue.pop_timeout(Duration::from_secs(10)),
+        Err(PopTimeoutError::Closed)
+    );
+}
+
+// cargo test --package lf-queue --test queue -- test_close_wakes_consumers --exact --nocapture
+#[test]
+fn test_close_wakes_consumers() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    let queue: Queue<usize> = Queue::new();
+    let popped = Arc::new(AtomicUsize::new(0));
+
+    let consumers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            let p = popped.clone();
+            thread::spawn(move || {
+                while q.pop_blocking().is_some() {
+                    p.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        })
+        .collect();
+
+    let producers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    q.push(i);
+                }
+            })
+        })
+        .collect();
+
+    for th in producers {
+        th.join().unwrap();
+    }
+
+    queue.close();
+
+    for th in consumers {
+        th.join().unwrap();
+    }
+
+    assert_eq!(popped.load(Ordering::SeqCst), COUNT * CONCURRENCY);
+}
+
+struct DropCounter<'a>(&'a AtomicUsize);
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// cargo test --package lf-queue --test queue -- test_drop_remaining_items --exact --nocapture
+#[test]
+fn test_drop_remaining_items() {
+    const COUNT: usize = 7 * 3 + 2;
+    const POPPED: usize = 10;
+    let drops = AtomicUsize::new(0);
+    let queue: Queue<_, 7> = Queue::new();
+
+    for _ in 0..COUNT {
+        queue.push(DropCounter(&drops));
+    }
+
+    for _ in 0..POPPED {
+        drop(queue.pop().unwrap());
+    }
+
+    assert_eq!(drops.load(Ordering::SeqCst), POPPED);
+
+    drop(queue);
+
+    assert_eq!(drops.load(Ordering::SeqCst), COUNT);
+}
+
+// cargo test --package lf-queue --test queue -- test_drop_last_handle --exact --nocapture
+#[test]
+fn test_drop_last_handle() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    let popped = Arc::new(AtomicUsize::new(0));
+    let queue: Queue<DropCounter<'static>> = Queue::new();
+
+    let ths: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            let q = queue.clone();
+            let p = popped.clone();
+            thread::spawn(move || {
+                for _ in 0..COUNT {
+                    q.push(DropCounter(&DROPS));
+                }
+                for _ in 0..COUNT / 2 {
+                    if q.pop().is_some() {
+                        p.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for th in ths {
+        th.join().unwrap();
+    }
+
+    assert_eq!(DROPS.load(Ordering::SeqCst), popped.load(Ordering::SeqCst));
+
+    drop(queue);
+
+    assert_eq!(DROPS.load(Ordering::SeqCst), COUNT * CONCURRENCY);
+}
+
+// cargo test --package lf-queue --test queue -- test_drop_empty --exact --nocapture
+#[test]
+fn test_drop_empty() {
+    let drops = AtomicUsize::new(0);
+    let queue: Queue<_, 7> = Queue::new();
+
+    for _ in 0..7 {
+        queue.push(DropCounter(&drops));
+    }
+
+    while queue.pop().is_some() {}
+
+    assert_eq!(drops.load(Ordering::SeqCst), 7);
+
+    drop(queue);
+
+    assert_eq!(drops.load(Ordering::SeqCst), 7);
+}
+
+// cargo test --package lf-queue --test queue -- test_node_capacity --exact --nocapture
+#[test]
+fn test_node_capacity() {
+    fn check<const N: usize>() {
+        const COUNT: usize = 100;
+        let queue: Queue<usize, N> = Queue::new();
+
+        for i in 0..COUNT {
+            queue.push(i);
+        }
+
+        for i in 0..COUNT {
+            assert_eq!(queue.pop(), Some(i));
+        }
+
+        assert!(queue.pop().is_none());
+    }
+
+    // Covers nodes holding a single item, and sizes which aren't a power of two.
+    check::<1>();
+    check::<2>();
+    check::<3>();
+    check::<31>();
+    check::<32>();
+    check::<100>();
+    check::<1024>();
+}
+
+// cargo test --package lf-queue --test queue -- test_wait_strategy --exact --nocapture
+#[test]
+fn test_wait_strategy() {
+    fn check<W: WaitStrategy + 'static>() {
+        const COUNT: usize = 1_000;
+        const CONCURRENCY: usize = 2;
+
+        // Small nodes make the threads wait for the next node to be installed more often.
+        let queue: Queue<usize, 7, W> = Queue::new();
+
+        let producers: Vec<_> = (0..CONCURRENCY)
+            .map(|_| {
+                let q = queue.clone();
+                thread::spawn(move || {
+                    for i in 0..COUNT {
+                        q.push(i);
+                    }
+                })
+            })
+            .collect();
+
+        let consumers: Vec<_> = (0..CONCURRENCY)
+            .map(|_| {
+                let q = queue.clone();
+                thread::spawn(move || {
+                    let mut sum = 0;
+                    while let Some(x) = q.pop_blocking() {
+                        sum += x;
+                    }
+                    sum
+                })
+            })
+            .collect();
+
+        for th in producers {
+            th.join().unwrap();
+        }
+
+        queue.close();
+
+        let sum: usize = consumers.into_iter().map(|th| th.join().unwrap()).sum();
+        assert_eq!(sum, CONCURRENCY * COUNT * (COUNT - 1) / 2);
+    }
+
+    check::<Spin>();
+    check::<Backoff>();
+    check::<Yield>();
+    check::<ParkAfter<0>>();
+    check::<ParkAfter<100>>();
+}
+
+// cargo test --package lf-queue --test queue -- test_debug --exact --nocapture
+#[test]
+fn test_debug() {
+    // The items aren't required to implement `Debug`, as they're never reported.
+    struct Item;
+
+    let queue: Queue<Item, 3> = Queue::new();
+    assert_eq!(
+        format!("{:?}", queue),
+        "Queue { len: 0, head: 0, tail: 0, closed: false }"
+    );
+
+    for _ in 0..5 {
+        queue.push(Item);
+    }
+    queue.pop();
+    queue.pop();
+    assert!(queue.close());
+
+    assert_eq!(
+        format!("{:?}", queue),
+        "Queue { len: 3, head: 2, tail: 5, closed: true }"
+    );
+}
+
+// cargo test --package lf-queue --test queue -- test_debug_dump --exact --nocapture
+#[test]
+fn test_debug_dump() {
+    let queue: Queue<usize, 3> = Queue::new();
+    assert_eq!(
+        queue.debug_dump(),
+        "Queue { len: 0, head: 0, tail: 0, closed: false }\n"
+    );
+
+    queue.push_batch(0..4);
+    assert_eq!(queue.pop(), Some(0));
+    assert_eq!(
+        queue.debug_dump(),
+        "Queue { len: 3, head: 1, tail: 4, closed: false }\n\
+         node 0: [FILLED|READING, FILLED, FILLED]\n\
+         node 1: [FILLED, EMPTY, EMPTY]\n"
+    );
+
+    // Once drained, the head node is no longer part of the chain.
+    assert_eq!(queue.pop(), Some(1));
+    assert_eq!(queue.pop(), Some(2));
+    assert_eq!(
+        queue.debug_dump(),
+        "Queue { len: 1, head: 3, tail: 4, closed: false }\n\
+         node 0: [FILLED, EMPTY, EMPTY]\n"
+    );
+}
@@ -0,0 +1,138 @@
+#![cfg(feature = "std")]
+
+use lf_queue::{PopTimeoutError, PushError, RawQueue, TryPopError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+// cargo test --package lf-queue --test raw -- test_scoped --exact --nocapture
+#[test]
+fn test_scoped() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    let queue: RawQueue<usize, 7> = RawQueue::new();
+
+    assert!(queue.is_empty());
+    assert_eq!(queue.try_pop(), Err(TryPopError::Empty));
+
+    let sum = thread::scope(|s| {
+        let consumers: Vec<_> = (0..CONCURRENCY)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut sum = 0;
+                    while let Some(x) = queue.pop_blocking() {
+                        sum += x;
+                    }
+                    sum
+                })
+            })
+            .collect();
+
+        let producers: Vec<_> = (0..CONCURRENCY)
+            .map(|_| {
+                s.spawn(|| {
+                    for i in 0..COUNT {
+                        queue.push(i);
+                    }
+                })
+            })
+            .collect();
+
+        for th in producers {
+            th.join().unwrap();
+        }
+
+        assert!(queue.close());
+
+        consumers
+            .into_iter()
+            .map(|th| th.join().unwrap())
+            .sum::<usize>()
+    });
+
+    assert_eq!(sum, CONCURRENCY * COUNT * (COUNT - 1) / 2);
+
+    assert_eq!(queue.try_push(1), Err(PushError::Closed(1)));
+    assert_eq!(
+        queue.pop_timeout(Duration::from_millis(10)),
+        Err(PopTimeoutError::Closed)
+    );
+}
+
+// cargo test --package lf-queue --test raw -- test_batch_and_iterators --exact --nocapture
+#[test]
+fn test_batch_and_iterators() {
+    let queue: RawQueue<usize, 7> = RawQueue::new();
+
+    queue.push_batch(0..20);
+    assert_eq!(queue.len(), 20);
+
+    let mut buf = Vec::new();
+    assert_eq!(queue.pop_batch(&mut buf, 5), 5);
+    assert_eq!(buf, (0..5).collect::<Vec<_>>());
+
+    assert_eq!(
+        queue.drain().take(5).collect::<Vec<_>>(),
+        (5..10).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        queue.try_iter().collect::<Vec<_>>(),
+        (10..20).collect::<Vec<_>>()
+    );
+    assert!(queue.is_empty());
+}
+
+// cargo test --package lf-queue --test raw -- test_get_mut --exact --nocapture
+#[test]
+fn test_get_mut() {
+    let mut queue: RawQueue<usize, 7> = RawQueue::new();
+
+    let mut q = queue.get_mut();
+    for i in 0..20 {
+        q.push(i);
+    }
+    assert_eq!(q.len(), 20);
+    for i in 0..10 {
+        assert_eq!(q.pop(), Some(i));
+    }
+
+    // Items pushed through the exclusive access are visible to shared accesses.
+    thread::scope(|s| {
+        s.spawn(|| {
+            for i in 10..20 {
+                assert_eq!(queue.pop(), Some(i));
+            }
+        });
+    });
+
+    assert!(queue.is_empty());
+}
+
+struct DropCounter<'a>(&'a AtomicUsize);
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// cargo test --package lf-queue --test raw -- test_drop_remaining_items --exact --nocapture
+#[test]
+fn test_drop_remaining_items() {
+    let drops = AtomicUsize::new(0);
+
+    // Dropping a queue without any node.
+    drop(RawQueue::<DropCounter<'_>>::new());
+
+    let queue: RawQueue<DropCounter<'_>, 7> = RawQueue::new();
+    for _ in 0..20 {
+        queue.push(DropCounter(&drops));
+    }
+    for _ in 0..5 {
+        drop(queue.pop());
+    }
+
+    assert_eq!(drops.load(Ordering::SeqCst), 5);
+    drop(queue);
+    assert_eq!(drops.load(Ordering::SeqCst), 20);
+}
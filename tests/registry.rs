@@ -0,0 +1,111 @@
+#![cfg(feature = "registry")]
+
+// Tests run concurrently and share the registry, so each one registers its queues
+// under its own names and ignores the others.
+
+use lf_queue::registry::{self, QueueSnapshot};
+use lf_queue::Queue;
+use std::thread;
+
+/// Returns the registered queues whose name starts with `prefix`.
+fn registered(prefix: &str) -> Vec<QueueSnapshot> {
+    registry::snapshot()
+        .into_iter()
+        .filter(|q| q.name.starts_with(prefix))
+        .collect()
+}
+
+// cargo test --features registry --package lf-queue --test registry -- test_snapshot --exact --nocapture
+#[test]
+fn test_snapshot() {
+    let queue: Queue<usize> = Queue::named("snapshot");
+    queue.push_batch(0..10);
+    assert_eq!(queue.pop(), Some(0));
+
+    let snapshot = registered("snapshot");
+    assert_eq!(snapshot.len(), 1);
+    assert_eq!(snapshot[0].name, "snapshot");
+    assert_eq!(snapshot[0].stats.len, 9);
+    assert_eq!(snapshot[0].stats.pushes, 10);
+    assert_eq!(snapshot[0].stats.pops, 1);
+}
+
+// cargo test --features registry --package lf-queue --test registry -- test_registration_order --exact --nocapture
+#[test]
+fn test_registration_order() {
+    let first: Queue<usize> = Queue::named("order-first");
+    let second: Queue<String> = Queue::named("order-second");
+    let third: Queue<usize> = Queue::named("order-third");
+    second.push(String::from("item"));
+
+    let names: Vec<_> = registered("order-").into_iter().map(|q| q.name).collect();
+    assert_eq!(names, ["order-first", "order-second", "order-third"]);
+
+    drop(second);
+    let names: Vec<_> = registered("order-").into_iter().map(|q| q.name).collect();
+    assert_eq!(names, ["order-first", "order-third"]);
+
+    drop((first, third));
+    assert!(registered("order-").is_empty());
+}
+
+// cargo test --features registry --package lf-queue --test registry -- test_last_handle --exact --nocapture
+#[test]
+fn test_last_handle() {
+    const CONCURRENCY: usize = 4;
+    let queue: Queue<usize> = Queue::named("handles");
+
+    // The queue stays registered as long as one of its handles is alive, whichever
+    // thread drops it.
+    let ths: Vec<_> = (0..CONCURRENCY)
+        .map(|i| {
+            let q = queue.clone();
+            thread::spawn(move || q.push(i))
+        })
+        .collect();
+    drop(queue);
+    assert!(registered("handles").len() <= 1);
+
+    for th in ths {
+        th.join().unwrap();
+    }
+    assert!(registered("handles").is_empty());
+}
+
+// cargo test --features registry --package lf-queue --test registry -- test_exclusive --exact --nocapture
+#[test]
+fn test_exclusive() {
+    // The registry doesn't hold a handle of the queue, so a single handle still
+    // gives exclusive access to it.
+    let mut queue: Queue<usize> = Queue::named("exclusive");
+    queue.get_mut().unwrap().push(1);
+    assert_eq!(registered("exclusive")[0].stats.len, 1);
+
+    let other = queue.clone();
+    assert!(queue.get_mut().is_none());
+    drop(other);
+    assert!(queue.get_mut().is_some());
+
+    // Taking the items out of the queue consumes it, which removes it from the
+    // registry.
+    assert_eq!(queue.try_into_vec().unwrap(), [1]);
+    assert!(registered("exclusive").is_empty());
+}
+
+// cargo test --features registry --package lf-queue --test registry -- test_write_openmetrics --exact --nocapture
+#[test]
+fn test_write_openmetrics() {
+    let ingest: Queue<usize> = Queue::named("metrics-ingest");
+    let egress: Queue<usize> = Queue::named("metrics-egress");
+    ingest.push_batch(0..3);
+    egress.push(1);
+
+    let mut out = String::new();
+    registry::write_openmetrics(&mut out).unwrap();
+
+    // Each family is described once, followed by the samples of every queue.
+    assert_eq!(out.matches("# TYPE lf_queue_length gauge\n").count(), 1);
+    assert!(out.contains("lf_queue_length{queue=\"metrics-ingest\"} 3\n"));
+    assert!(out.contains("lf_queue_length{queue=\"metrics-egress\"} 1\n"));
+    assert!(out.ends_with("# EOF\n"));
+}
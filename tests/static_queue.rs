@@ -0,0 +1,97 @@
+#![cfg(all(feature = "std", not(loom)))]
+
+use lf_queue::{PopTimeoutError, PushError, StaticQueue, TryPopError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+// cargo test --package lf-queue --test static_queue -- test_static --exact --nocapture
+#[test]
+fn test_static() {
+    const COUNT: usize = 1_000;
+    const CONCURRENCY: usize = 4;
+    static QUEUE: StaticQueue<usize, 7> = StaticQueue::new();
+
+    assert!(QUEUE.is_empty());
+    assert_eq!(QUEUE.try_pop(), Err(TryPopError::Empty));
+
+    let producers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            thread::spawn(|| {
+                for i in 0..COUNT {
+                    QUEUE.push(i);
+                }
+            })
+        })
+        .collect();
+
+    let consumers: Vec<_> = (0..CONCURRENCY)
+        .map(|_| {
+            thread::spawn(|| {
+                let mut sum = 0;
+                while let Some(x) = QUEUE.pop_blocking() {
+                    sum += x;
+                }
+                sum
+            })
+        })
+        .collect();
+
+    for th in producers {
+        th.join().unwrap();
+    }
+
+    assert!(QUEUE.close());
+
+    let sum: usize = consumers.into_iter().map(|th| th.join().unwrap()).sum();
+    assert_eq!(sum, CONCURRENCY * COUNT * (COUNT - 1) / 2);
+
+    assert_eq!(QUEUE.try_push(1), Err(PushError::Closed(1)));
+    assert_eq!(
+        QUEUE.pop_timeout(Duration::from_millis(10)),
+        Err(PopTimeoutError::Closed)
+    );
+}
+
+// cargo test --package lf-queue --test static_queue -- test_batch --exact --nocapture
+#[test]
+fn test_batch() {
+    let queue: StaticQueue<usize, 7> = StaticQueue::new();
+
+    queue.push_batch(0..20);
+    assert_eq!(queue.len(), 20);
+
+    let mut buf = Vec::new();
+    assert_eq!(queue.pop_batch(&mut buf, 30), 20);
+    assert_eq!(buf, (0..20).collect::<Vec<_>>());
+    assert!(queue.is_empty());
+}
+
+struct DropCounter<'a>(&'a AtomicUsize);
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// cargo test --package lf-queue --test static_queue -- test_drop_remaining_items --exact --nocapture
+#[test]
+fn test_drop_remaining_items() {
+    let drops = AtomicUsize::new(0);
+
+    // Dropping a queue without any node.
+    drop(StaticQueue::<DropCounter<'_>>::new());
+
+    let queue: StaticQueue<DropCounter<'_>, 7> = StaticQueue::new();
+    for _ in 0..20 {
+        queue.push(DropCounter(&drops));
+    }
+    for _ in 0..5 {
+        drop(queue.pop());
+    }
+
+    assert_eq!(drops.load(Ordering::SeqCst), 5);
+    drop(queue);
+    assert_eq!(drops.load(Ordering::SeqCst), 20);
+}